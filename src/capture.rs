@@ -2,58 +2,183 @@ use anyhow::{Context, Result};
 use image::save_buffer;
 use image::ColorType::Rgba8;
 use std::borrow::Borrow;
-use std::ops::{Add, Sub};
+use std::cmp::{max, min};
 use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tempfile::TempDir;
 
+use crate::common::image::{crop_region, stitch_horizontally};
+use crate::common::Rect;
 use crate::utils::{file_name_for, IMG_EXT};
 use crate::{ImageOnHeap, PlatformApi, WindowId};
 
+const FRAME_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_FRAME_INTERVAL: Duration = Duration::from_millis(1000);
+/// polling for changes is cheap, since only changed frames get saved
+const ON_CHANGE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// insights about a finished capture
+pub struct CaptureStats {
+    /// all screenshots taken, including the idle ones that got dropped
+    pub frames: usize,
+    pub duration: Duration,
+    /// time spent taking and saving screenshots
+    pub busy: Duration,
+}
+
+impl CaptureStats {
+    /// the average frame rate that was achieved
+    pub fn fps(&self) -> f64 {
+        if self.duration.is_zero() {
+            0.0
+        } else {
+            self.frames as f64 / self.duration.as_secs_f64()
+        }
+    }
+}
+
+/// a simple controller that stretches the interval between two frames,
+/// when taking a frame eats up most of the interval (e.g. on a busy machine),
+/// and goes back to the base interval once there is enough headroom again
+struct AdaptiveInterval {
+    base: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl AdaptiveInterval {
+    fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            current: base,
+        }
+    }
+
+    fn update(&mut self, frame_time: Duration) {
+        if frame_time > self.current / 2 {
+            self.current = min(self.current * 3 / 2, self.max);
+        } else if frame_time < self.current / 4 {
+            self.current = max(self.current * 2 / 3, self.base);
+        }
+    }
+}
+
+/// turns the wall clock time of a frame into its time code.
+/// Every stretch of identical frames longer than the idle threshold gets cut out of the timeline,
+/// so that an idle period is shown for the idle pause, or one frame interval if there is none.
+/// This is the same for the very beginning: the gap before the first change is collapsed
+/// like any other idle period, the first frame itself keeps its real time code.
+struct Timeline {
+    start: Instant,
+    /// the time that got cut out so far
+    cut: Duration,
+    /// when the last non-idle frame was taken
+    last_kept: Instant,
+    /// when the last frame, idle or not, was taken
+    last: Instant,
+    threshold: Duration,
+    pause: Option<Duration>,
+}
+
+impl Timeline {
+    fn new(start: Instant, threshold: Duration, pause: Option<Duration>) -> Self {
+        Self {
+            start,
+            cut: Duration::default(),
+            last_kept: start,
+            last: start,
+            threshold,
+            pause,
+        }
+    }
+
+    /// advances the timeline to a frame taken at `now`,
+    /// returns the time code of the frame, which is only meaningful for non-idle frames
+    fn advance(&mut self, now: Instant, idle: bool) -> u128 {
+        if !idle {
+            let idle_time = self.last.duration_since(self.last_kept);
+            if idle_time > self.threshold {
+                self.cut += match self.pause {
+                    Some(pause) => now.duration_since(self.last_kept).saturating_sub(pause),
+                    None => idle_time,
+                };
+            }
+            self.last_kept = now;
+        }
+        self.last = now;
+        now.checked_sub(self.cut)
+            .unwrap_or(self.start)
+            .saturating_duration_since(self.start)
+            .as_millis()
+    }
+}
+
+/// options that change how frames are captured
+pub struct CaptureOptions {
+    /// disables the idle detection and sampling optimization
+    pub natural: bool,
+    /// lowers the frame rate, when capturing can't keep up
+    pub adaptive_fps: bool,
+    /// the gap in pixels between windows, when more than one window is captured
+    pub gap: u32,
+    /// how long the window must not change, to count as idle
+    pub idle_threshold: Duration,
+    /// how long an idle period is shown, `None` for one frame interval
+    pub idle_pause: Option<Duration>,
+    /// stops capturing on its own, once that many frames are kept
+    pub max_frames: Option<usize>,
+    /// polls at a high rate, but keeps only changed frames with their real time codes
+    pub on_change: bool,
+    /// keeps only changed frames with their real time codes, at the regular rate,
+    /// so that identical frames are stored once and the previous frame is shown longer
+    pub dedup: bool,
+    /// captures only this part of the window, relative to its calibrated bounds
+    pub region: Option<Rect>,
+}
+
 /// captures screenshots as file on disk
 /// collects also the timecodes when they have been captured
-/// stops once receiving something in rx
-pub fn capture_thread(
+/// stops once receiving something in rx, or once `max_frames` are kept
+/// with more than one target, the windows get composed side-by-side into one frame
+pub fn capture_thread<A: PlatformApi>(
     rx: &Receiver<()>,
-    api: impl PlatformApi,
-    win_id: WindowId,
+    targets: Vec<(A, WindowId)>,
     time_codes: Arc<Mutex<Vec<u128>>>,
     tempdir: Arc<Mutex<TempDir>>,
-    force_natural: bool,
-) -> Result<()> {
-    let duration = Duration::from_millis(250);
+    options: CaptureOptions,
+) -> Result<CaptureStats> {
+    // in on-change and dedup mode nothing is idle, pauses keep their real length
+    let (base_interval, idle_threshold) = if options.on_change {
+        (ON_CHANGE_INTERVAL, Duration::MAX)
+    } else if options.dedup {
+        (FRAME_INTERVAL, Duration::MAX)
+    } else {
+        (FRAME_INTERVAL, options.idle_threshold)
+    };
+    let mut interval = AdaptiveInterval::new(base_interval, MAX_FRAME_INTERVAL);
+    let mut frames = 0;
+    let mut kept = 0;
+    let mut busy = Duration::default();
     let start = Instant::now();
-    let mut idle_duration = Duration::from_millis(0);
+    let mut timeline = Timeline::new(start, idle_threshold, options.idle_pause);
     let mut last_frame: Option<ImageOnHeap> = None;
-    let mut identical_frames = 0;
-    let mut last_now = Instant::now();
     loop {
         // blocks for a timeout
-        if rx.recv_timeout(duration).is_ok() {
+        if rx.recv_timeout(interval.current).is_ok() {
             break;
         }
         let now = Instant::now();
-        let effective_now = now.sub(idle_duration);
-        let tc = effective_now.saturating_duration_since(start).as_millis();
-        let image = api.capture_window_screenshot(win_id)?;
-        if !force_natural {
-            if last_frame.is_some()
-                && image
-                    .samples
-                    .as_slice()
-                    .eq(last_frame.as_ref().unwrap().samples.as_slice())
-            {
-                identical_frames += 1;
-            } else {
-                identical_frames = 0;
-            }
-        }
+        let image = capture_frame(&targets, options.gap, options.region.as_ref())?;
+        frames += 1;
+        let idle = (!options.natural || options.dedup)
+            && last_frame
+                .as_ref()
+                .is_some_and(|last| image.samples.as_slice().eq(last.samples.as_slice()));
+        let tc = timeline.advance(now, idle);
 
-        if identical_frames > 0 {
-            // let's track now the duration as idle
-            idle_duration = idle_duration.add(now.duration_since(last_now));
-        } else {
+        if !idle {
             if let Err(e) = save_frame(&image, tc, tempdir.lock().unwrap().borrow(), file_name_for)
             {
                 eprintln!("{}", &e);
@@ -61,12 +186,46 @@ pub fn capture_thread(
             }
             time_codes.lock().unwrap().push(tc);
             last_frame = Some(image);
-            identical_frames = 0;
+            kept += 1;
+        }
+        let frame_time = now.elapsed();
+        busy += frame_time;
+        if options.adaptive_fps {
+            interval.update(frame_time);
+        }
+        if options.max_frames.is_some_and(|max| kept >= max) {
+            break;
         }
-        last_now = now;
     }
 
-    Ok(())
+    Ok(CaptureStats {
+        frames,
+        duration: start.elapsed(),
+        busy,
+    })
+}
+
+/// captures all targets, and stitches them side-by-side if there is more than one,
+/// then crops the region out of it, if there is one
+pub fn capture_frame(
+    targets: &[(impl PlatformApi, WindowId)],
+    gap: u32,
+    region: Option<&Rect>,
+) -> Result<ImageOnHeap> {
+    let image = if let [(api, win_id)] = targets {
+        api.capture_window_screenshot(*win_id)?
+    } else {
+        let images = targets
+            .iter()
+            .map(|(api, win_id)| api.capture_window_screenshot(*win_id))
+            .collect::<Result<Vec<_>>>()?;
+        stitch_horizontally(&images, gap)?
+    };
+
+    match region {
+        Some(region) => crop_region(*image, region),
+        None => Ok(image),
+    }
 }
 
 /// saves a frame as a tga file
@@ -85,3 +244,325 @@ pub fn save_frame(
     )
     .context("Cannot save frame")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::MockPlatformApi;
+    use std::sync::mpsc;
+
+    #[test]
+    fn should_stretch_interval_when_cpu_bound() {
+        let mut interval = AdaptiveInterval::new(FRAME_INTERVAL, MAX_FRAME_INTERVAL);
+
+        interval.update(Duration::from_millis(200));
+        assert_eq!(interval.current, Duration::from_millis(375));
+
+        for _ in 0..10 {
+            interval.update(Duration::from_millis(900));
+        }
+        assert_eq!(interval.current, MAX_FRAME_INTERVAL);
+    }
+
+    #[test]
+    fn should_recover_interval_with_enough_headroom() {
+        let mut interval = AdaptiveInterval::new(FRAME_INTERVAL, MAX_FRAME_INTERVAL);
+        interval.update(Duration::from_millis(200));
+
+        interval.update(Duration::from_millis(10));
+        assert_eq!(interval.current, FRAME_INTERVAL);
+
+        interval.update(Duration::from_millis(10));
+        assert_eq!(interval.current, FRAME_INTERVAL);
+    }
+
+    #[test]
+    fn should_keep_interval_in_between() {
+        let mut interval = AdaptiveInterval::new(FRAME_INTERVAL, MAX_FRAME_INTERVAL);
+
+        interval.update(Duration::from_millis(100));
+        assert_eq!(interval.current, FRAME_INTERVAL);
+    }
+
+    #[test]
+    fn should_calculate_fps() {
+        let stats = CaptureStats {
+            frames: 20,
+            duration: Duration::from_secs(5),
+            busy: Duration::from_secs(1),
+        };
+        assert_eq!(stats.fps(), 4.0);
+    }
+
+    #[test]
+    fn should_capture_frames_from_mock_api() -> Result<()> {
+        // given a window that changes once and then stays the same
+        let (tx, rx) = mpsc::channel();
+        let api = MockPlatformApi::new(vec![
+            MockPlatformApi::frame(4, 3, [0, 0, 0, 0xff]),
+            MockPlatformApi::frame(4, 3, [0xff, 0, 0, 0xff]),
+            MockPlatformApi::frame(4, 3, [0xff, 0, 0, 0xff]),
+        ])
+        .stop_when_exhausted(tx);
+        let time_codes = Arc::new(Mutex::new(vec![]));
+        let tempdir = Arc::new(Mutex::new(TempDir::new()?));
+        let options = CaptureOptions {
+            natural: false,
+            adaptive_fps: false,
+            gap: 0,
+            idle_threshold: Duration::ZERO,
+            idle_pause: None,
+            max_frames: None,
+            on_change: false,
+            dedup: false,
+            region: None,
+        };
+
+        // when
+        let stats = capture_thread(
+            &rx,
+            vec![(api, 1)],
+            time_codes.clone(),
+            tempdir.clone(),
+            options,
+        )?;
+
+        // then the identical frame is dropped
+        assert_eq!(stats.frames, 3);
+        let time_codes = time_codes.lock().unwrap();
+        assert_eq!(time_codes.len(), 2);
+        for tc in time_codes.iter() {
+            let frame = tempdir
+                .lock()
+                .unwrap()
+                .path()
+                .join(file_name_for(tc, IMG_EXT));
+            assert!(frame.exists());
+        }
+
+        Ok(())
+    }
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn should_keep_timeline_without_idle() {
+        let start = Instant::now();
+        let mut timeline = Timeline::new(start, Duration::ZERO, None);
+
+        assert_eq!(timeline.advance(ms(start, 250), false), 250);
+        assert_eq!(timeline.advance(ms(start, 500), false), 500);
+        assert_eq!(timeline.advance(ms(start, 750), false), 750);
+    }
+
+    #[test]
+    fn should_collapse_idle_in_the_middle() {
+        let start = Instant::now();
+        let mut timeline = Timeline::new(start, Duration::ZERO, None);
+
+        assert_eq!(timeline.advance(ms(start, 250), false), 250);
+        timeline.advance(ms(start, 500), true);
+        timeline.advance(ms(start, 750), true);
+        // one interval after the last non-idle frame
+        assert_eq!(timeline.advance(ms(start, 1000), false), 500);
+        assert_eq!(timeline.advance(ms(start, 1250), false), 750);
+    }
+
+    #[test]
+    fn should_collapse_idle_at_start() {
+        let start = Instant::now();
+        let mut timeline = Timeline::new(start, Duration::ZERO, None);
+
+        // the first frame keeps its time code, waiting for the first keystroke is idle
+        assert_eq!(timeline.advance(ms(start, 250), false), 250);
+        for t in (500..5000).step_by(250) {
+            timeline.advance(ms(start, t), true);
+        }
+        assert_eq!(timeline.advance(ms(start, 5000), false), 500);
+    }
+
+    #[test]
+    fn should_collapse_idle_at_end() {
+        let start = Instant::now();
+        let mut timeline = Timeline::new(start, Duration::ZERO, None);
+
+        assert_eq!(timeline.advance(ms(start, 250), false), 250);
+        assert_eq!(timeline.advance(ms(start, 500), false), 500);
+        timeline.advance(ms(start, 750), true);
+        timeline.advance(ms(start, 1000), true);
+
+        // nothing to cut, since no frame follows, the end pause takes over
+        assert_eq!(timeline.cut, Duration::ZERO);
+    }
+
+    #[test]
+    fn should_keep_idle_below_threshold() {
+        let start = Instant::now();
+        let mut timeline = Timeline::new(start, Duration::from_millis(1000), None);
+
+        assert_eq!(timeline.advance(ms(start, 250), false), 250);
+        for t in (500..1250).step_by(250) {
+            timeline.advance(ms(start, t), true);
+        }
+        // a short natural pause of 750ms is kept
+        assert_eq!(timeline.advance(ms(start, 1250), false), 1250);
+        for t in (1500..4000).step_by(250) {
+            timeline.advance(ms(start, t), true);
+        }
+        // a long pause is collapsed
+        assert_eq!(timeline.advance(ms(start, 4000), false), 1500);
+    }
+
+    #[test]
+    fn should_keep_real_time_codes_on_change() {
+        let start = Instant::now();
+        let mut timeline = Timeline::new(start, Duration::MAX, None);
+
+        assert_eq!(timeline.advance(ms(start, 50), false), 50);
+        for t in (100..3000).step_by(50) {
+            timeline.advance(ms(start, t), true);
+        }
+        assert_eq!(timeline.advance(ms(start, 3000), false), 3000);
+    }
+
+    #[test]
+    fn should_replace_idle_with_pause() {
+        let start = Instant::now();
+        let mut timeline = Timeline::new(start, Duration::ZERO, Some(Duration::from_millis(600)));
+
+        assert_eq!(timeline.advance(ms(start, 250), false), 250);
+        for t in (500..5000).step_by(250) {
+            timeline.advance(ms(start, t), true);
+        }
+        assert_eq!(timeline.advance(ms(start, 5000), false), 850);
+        // without idle nothing changes
+        assert_eq!(timeline.advance(ms(start, 5250), false), 1100);
+    }
+
+    #[test]
+    fn should_collapse_leading_idle_when_capturing() -> Result<()> {
+        // given a window that does not change for a while, before the first keystroke
+        let (tx, rx) = mpsc::channel();
+        let idle = MockPlatformApi::frame(4, 3, [0, 0, 0, 0xff]);
+        let api = MockPlatformApi::new(vec![
+            idle.clone(),
+            idle.clone(),
+            idle.clone(),
+            idle,
+            MockPlatformApi::frame(4, 3, [0xff, 0, 0, 0xff]),
+        ])
+        .stop_when_exhausted(tx);
+        let time_codes = Arc::new(Mutex::new(vec![]));
+        let tempdir = Arc::new(Mutex::new(TempDir::new()?));
+        let options = CaptureOptions {
+            natural: false,
+            adaptive_fps: false,
+            gap: 0,
+            idle_threshold: Duration::ZERO,
+            idle_pause: None,
+            max_frames: None,
+            on_change: false,
+            dedup: false,
+            region: None,
+        };
+
+        // when
+        capture_thread(&rx, vec![(api, 1)], time_codes.clone(), tempdir, options)?;
+
+        // then the 3 idle frames are gone and so is their time
+        let time_codes = time_codes.lock().unwrap();
+        assert_eq!(time_codes.len(), 2);
+        assert!(time_codes[1] - time_codes[0] < 2 * FRAME_INTERVAL.as_millis());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_stop_capturing_at_max_frames() -> Result<()> {
+        // given more frames than wanted and nobody who stops the capture
+        let (_tx, rx) = mpsc::channel();
+        let api = MockPlatformApi::new(vec![
+            MockPlatformApi::frame(4, 3, [0, 0, 0, 0xff]),
+            MockPlatformApi::frame(4, 3, [0xff, 0, 0, 0xff]),
+            MockPlatformApi::frame(4, 3, [0, 0xff, 0, 0xff]),
+        ]);
+        let time_codes = Arc::new(Mutex::new(vec![]));
+        let tempdir = Arc::new(Mutex::new(TempDir::new()?));
+        let options = CaptureOptions {
+            natural: false,
+            adaptive_fps: false,
+            gap: 0,
+            idle_threshold: Duration::ZERO,
+            idle_pause: None,
+            max_frames: Some(2),
+            on_change: false,
+            dedup: false,
+            region: None,
+        };
+
+        // when
+        let stats = capture_thread(&rx, vec![(api, 1)], time_codes.clone(), tempdir, options)?;
+
+        // then
+        assert_eq!(stats.frames, 2);
+        assert_eq!(time_codes.lock().unwrap().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_real_time_of_identical_frames_when_deduplicating() -> Result<()> {
+        // given a window that does not change for a while, even with natural timing
+        let (tx, rx) = mpsc::channel();
+        let idle = MockPlatformApi::frame(4, 3, [0, 0, 0, 0xff]);
+        let api = MockPlatformApi::new(vec![
+            idle.clone(),
+            idle.clone(),
+            idle.clone(),
+            idle,
+            MockPlatformApi::frame(4, 3, [0xff, 0, 0, 0xff]),
+        ])
+        .stop_when_exhausted(tx);
+        let time_codes = Arc::new(Mutex::new(vec![]));
+        let tempdir = Arc::new(Mutex::new(TempDir::new()?));
+        let options = CaptureOptions {
+            natural: true,
+            adaptive_fps: false,
+            gap: 0,
+            idle_threshold: Duration::ZERO,
+            idle_pause: None,
+            max_frames: None,
+            on_change: false,
+            dedup: true,
+            region: None,
+        };
+
+        // when
+        capture_thread(&rx, vec![(api, 1)], time_codes.clone(), tempdir, options)?;
+
+        // then the 3 identical frames are gone, but their time is not
+        let time_codes = time_codes.lock().unwrap();
+        assert_eq!(time_codes.len(), 2);
+        assert!(time_codes[1] - time_codes[0] >= 3 * FRAME_INTERVAL.as_millis());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_check_region_against_the_stitched_frame() {
+        let frame = || MockPlatformApi::frame(4, 3, [0, 0, 0, 0xff]);
+        let targets = || {
+            vec![
+                (MockPlatformApi::new(vec![frame()]), 1),
+                (MockPlatformApi::new(vec![frame()]), 2),
+            ]
+        };
+
+        // the second window is right of the gap, 10 pixels wide in total
+        let image = capture_frame(&targets(), 2, Some(&Rect::new(5, 0, 5, 3))).unwrap();
+        assert_eq!((image.layout.width, image.layout.height), (5, 3));
+        assert!(capture_frame(&targets(), 2, Some(&Rect::new(0, 0, 11, 3))).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs;
+use std::process::Command;
+
+use anyhow::Context;
+use image::Rgba;
+use tempfile::TempDir;
+
+use crate::common::identify_background::{as_hex_color, identify_background_color};
+use crate::utils::{file_name_for, IMG_EXT};
+use crate::Result;
+
+///
+/// renders a card frame with a centered text, as a regular frame for a given time code.
+/// The card gets the same dimensions and background color as the reference frame,
+/// so that it looks like it is part of the recording.
+///
+/// ```sh
+/// convert -size 800x600 xc:'#1e1e1e' \
+///     -fill '#ffffff' -gravity center -pointsize 48 \
+///     -annotate +0+0 'Hello World' \
+///     t-rec-frame-000000000.bmp
+/// ```
+pub fn render_card(text: &str, tempdir: &TempDir, reference_tc: u128, tc: u128) -> Result<()> {
+    let reference = tempdir.path().join(file_name_for(&reference_tc, IMG_EXT));
+    let reference = image::open(&reference)
+        .with_context(|| format!("Cannot open frame {:?} for the card", reference))?
+        .into_rgba8();
+    let (width, height) = reference.dimensions();
+    let bg =
+        identify_background_color(reference.into_flat_samples())?.unwrap_or(Rgba([0, 0, 0, 0xff]));
+    let fg = contrast_color(&bg);
+    // roughly 1/12 of the height, but not too tiny for small windows
+    let point_size = (height / 12).max(12);
+
+    let e = Command::new("convert")
+        .args(["-size", &format!("{}x{}", width, height)])
+        .arg(format!("xc:{}", as_hex_color(&bg)))
+        .args(["-fill", &as_hex_color(&fg)])
+        .args(["-gravity", "center"])
+        .args(["-pointsize", &point_size.to_string()])
+        .args(["-annotate", "+0+0", text])
+        .arg(tempdir.path().join(file_name_for(&tc, IMG_EXT)))
+        .output()
+        .context("Cannot render the card")?;
+
+    if !e.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&e.stderr))
+    } else {
+        Ok(())
+    }
+}
+
+///
+/// moves all frames to time codes that are `offset` milliseconds later,
+/// e.g. to make room for a title card in front of them
+pub fn shift_frames(time_codes: &mut [u128], tempdir: &TempDir, offset: u128) -> Result<()> {
+    // the last frame goes first, so that no frame is overwritten by its predecessor
+    for tc in time_codes.iter_mut().rev() {
+        let from = tempdir.path().join(file_name_for(tc, IMG_EXT));
+        *tc += offset;
+        let to = tempdir.path().join(file_name_for(tc, IMG_EXT));
+        if from.exists() {
+            fs::rename(&from, &to).with_context(|| format!("Cannot move frame {:?}", from))?;
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// shows the frame at `tc` a bit longer, with a copy of it at the later time code `hold_tc`
+pub fn hold_frame(tempdir: &TempDir, tc: u128, hold_tc: u128) -> Result<()> {
+    let frame = tempdir.path().join(file_name_for(&tc, IMG_EXT));
+    fs::copy(
+        &frame,
+        tempdir.path().join(file_name_for(&hold_tc, IMG_EXT)),
+    )
+    .with_context(|| format!("Cannot copy frame {:?}", frame))?;
+
+    Ok(())
+}
+
+/// black or white, whatever is better readable on the given background
+fn contrast_color(bg: &Rgba<u8>) -> Rgba<u8> {
+    let Rgba([r, g, b, _]) = bg;
+    let luminance = 0.299 * *r as f32 + 0.587 * *g as f32 + 0.114 * *b as f32;
+    if luminance > 127.0 {
+        Rgba([0, 0, 0, 0xff])
+    } else {
+        Rgba([0xff, 0xff, 0xff, 0xff])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_pick_readable_text_color() {
+        assert_eq!(
+            contrast_color(&Rgba([30, 30, 30, 0xff])),
+            Rgba([0xff, 0xff, 0xff, 0xff])
+        );
+        assert_eq!(
+            contrast_color(&Rgba([0xf0, 0xf0, 0xf0, 0xff])),
+            Rgba([0, 0, 0, 0xff])
+        );
+    }
+
+    #[test]
+    fn should_shift_frames_to_later_time_codes() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let mut time_codes = vec![0, 250, 500];
+        for tc in time_codes.iter() {
+            fs::write(
+                tempdir.path().join(file_name_for(tc, IMG_EXT)),
+                tc.to_string(),
+            )?;
+        }
+
+        shift_frames(&mut time_codes, &tempdir, 250)?;
+
+        assert_eq!(time_codes, vec![250, 500, 750]);
+        for (tc, content) in time_codes.iter().zip(["0", "250", "500"]) {
+            let frame = tempdir.path().join(file_name_for(tc, IMG_EXT));
+            assert_eq!(fs::read_to_string(frame)?, content);
+        }
+        assert!(!tempdir.path().join(file_name_for(&0, IMG_EXT)).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_hold_a_frame_with_a_copy() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        fs::write(tempdir.path().join(file_name_for(&500, IMG_EXT)), "500")?;
+
+        hold_frame(&tempdir, 500, 1500)?;
+
+        let copy = tempdir.path().join(file_name_for(&1500, IMG_EXT));
+        assert_eq!(fs::read_to_string(copy)?, "500");
+
+        Ok(())
+    }
+}
@@ -1,12 +1,30 @@
 use clap::builder::NonEmptyStringValueParser;
 use clap::{crate_authors, crate_description, crate_version, Arg, ArgAction, ArgMatches, Command};
 
+use crate::common::Rect;
+use crate::theme::theme_names;
+
 pub fn launch() -> ArgMatches {
     Command::new("t-rec")
         .allow_missing_positional(true)
         .version(crate_version!())
         .author(crate_authors!())
         .about(crate_description!())
+        .subcommand(
+            Command::new("encode")
+                .about("Generates the gif (or mp4) from a directory of existing frames without recording, e.g. to re-encode with different options")
+                .arg(
+                    Arg::new("dir")
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .value_name("frames dir")
+                        .required(true)
+                        .help("The directory with the frames, named like 't-rec-frame-000000250.bmp' where the number is the time code in ms"),
+                ),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("Checks that everything t-rec needs is in place, with hints how to fix what is missing")
+        )
         .arg(
             Arg::new("verbose")
                 .action(ArgAction::SetTrue)
@@ -15,6 +33,13 @@ pub fn launch() -> ArgMatches {
                 .required(false)
                 .help("Enable verbose insights for the curious")
         )
+        .arg(
+            Arg::new("about")
+                .action(ArgAction::SetTrue)
+                .long("about")
+                .required(false)
+                .help("Prints the version, the enabled features and the versions of the tools t-rec depends on, helpful for bug reports")
+        )
         .arg(
             Arg::new("quiet")
                 .action(ArgAction::SetTrue)
@@ -40,6 +65,27 @@ pub fn launch() -> ArgMatches {
                 .conflicts_with("video")
                 .help("Generates only a mp4 video and not gif")
         )
+        .arg(
+            Arg::new("apng")
+                .action(ArgAction::SetTrue)
+                .long("apng")
+                .required(false)
+                .help("Generates additionally an animated png (apng) in true colors, with the same timing as the gif")
+        )
+        .arg(
+            Arg::new("webm")
+                .action(ArgAction::SetTrue)
+                .long("webm")
+                .required(false)
+                .help("Generates additionally a webm video (VP9), that keeps transparent backgrounds transparent")
+        )
+        .arg(
+            Arg::new("avif")
+                .action(ArgAction::SetTrue)
+                .long("avif")
+                .required(false)
+                .help("Generates additionally an animated avif, much smaller than the gif but slow to encode")
+        )
         .arg(
             Arg::new("decor")
                 .value_parser(["shadow", "none"])
@@ -49,14 +95,90 @@ pub fn launch() -> ArgMatches {
                 .long("decor")
                 .help("Decorates the animation with certain, mostly border effects")
         )
+        .arg(
+            Arg::new("flip")
+                .value_parser(["horizontal", "vertical", "both"])
+                .required(false)
+                .long("flip")
+                .help("Mirrors the animation horizontally, vertically or both")
+        )
+        .arg(
+            Arg::new("rotate")
+                .value_parser(["90", "180", "270"])
+                .required(false)
+                .long("rotate")
+                .value_name("degrees")
+                .help("Rotates the animation clockwise, e.g. for recordings of a rotated display")
+        )
+        .arg(
+            Arg::new("max-width")
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .required(false)
+                .long("max-width")
+                .value_name("px")
+                .help("Downscales the animation to this width at most, preserving the aspect ratio, e.g. for a smaller gif")
+        )
+        .arg(
+            Arg::new("max-height")
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .required(false)
+                .long("max-height")
+                .value_name("px")
+                .help("Downscales the animation to this height at most, preserving the aspect ratio")
+        )
+        .arg(
+            Arg::new("resample")
+                .value_parser(["nearest", "triangle", "catmull-rom", "lanczos"])
+                .default_value("lanczos")
+                .required(false)
+                .long("resample")
+                .value_name("filter")
+                .help("The filter used when frames are scaled, `nearest` keeps pixel art crisp, `lanczos` is smooth but slower")
+        )
+        .arg(
+            Arg::new("gif-colors")
+                .value_parser(clap::value_parser!(u32).range(2..=256))
+                .required(false)
+                .long("gif-colors")
+                .value_name("n")
+                .help("Reduces the gif to at most n colors, fewer colors make smaller gifs (default: 256)")
+        )
+        .arg(
+            Arg::new("gif-dither")
+                .value_parser(["on", "off"])
+                .required(false)
+                .long("gif-dither")
+                .help("Turns dithering of the gif on or off, `off` avoids noise on flat colors, `on` smooths gradients")
+        )
+        .arg(
+            Arg::new("gif-global-palette")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("gif-global-palette")
+                .help("Uses one palette for all frames of the gif, instead of one per frame, so that colors don't flicker")
+        )
         .arg(
             Arg::new("bg")
-                .value_parser(["white", "black", "transparent"])
+                .value_parser(["white", "black", "transparent", "auto"])
                 .default_value("transparent")
                 .required(false)
                 .short('b')
                 .long("bg")
-                .help("Background color when decors are used")
+                .help("Background color when decors are used, `auto` picks the color from the edges of the recorded window")
+        )
+        .arg(
+            Arg::new("srgb")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("srgb")
+                .help("Normalizes the colors from the display color profile to sRGB, so that they look the same on other machines (takes extra time)")
+        )
+        .arg(
+            Arg::new("stats")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("stats")
+                .help("Prints how much time was spent in each phase, e.g. capture, effects and encoding")
         )
         .arg(
             Arg::new("natural-mode")
@@ -68,14 +190,81 @@ pub fn launch() -> ArgMatches {
                 .help("If you want a very natural typing experience and disable the idle detection and sampling optimization")
         )
         .arg(
-            Arg::new("list-windows")
+            Arg::new("idle-threshold")
+                .value_parser(NonEmptyStringValueParser::new())
+                .value_name("s | ms | m")
+                .required(false)
+                .long("idle-threshold")
+                .conflicts_with("natural-mode")
+                .help("to specify how long nothing must change to count as idle, shorter pauses are kept as they are (default: every pause is idle)"),
+        )
+        .arg(
+            Arg::new("idle-pause")
+                .value_parser(NonEmptyStringValueParser::new())
+                .value_name("s | ms | m")
+                .required(false)
+                .long("idle-pause")
+                .conflicts_with("natural-mode")
+                .help("to specify how long an idle period is shown in the animation (default: one frame)"),
+        )
+        .arg(
+            Arg::new("on-change")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("on-change")
+                .conflicts_with_all(["natural-mode", "idle-threshold", "idle-pause"])
+                .help("Captures a frame only when the window changes, with the real timing, great for slow tutorials with mostly static content"),
+        )
+        .arg(
+            Arg::new("dedup")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("dedup")
+                .conflicts_with_all(["idle-threshold", "idle-pause", "on-change"])
+                .help("Keeps the real timing like --natural, but stores identical frames only once, for smaller outputs of recordings with long pauses"),
+        )
+        .arg(
+            Arg::new("countdown")
+                .value_parser(clap::value_parser!(u64).range(1..=60))
+                .required(false)
+                .long("countdown")
+                .value_name("secs")
+                .help("Counts down the given seconds before the recording starts, to get ready for the first action"),
+        )
+        .arg(
+            Arg::new("frames")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .required(false)
+                .long("frames")
+                .value_name("n")
+                .help("Stops the recording after n frames and ends the shell or program, e.g. for bounded recordings in CI"),
+        )
+        .arg(
+            Arg::new("adaptive-fps")
                 .action(ArgAction::SetTrue)
-                .value_name("list all visible windows with name and id")
+                .required(false)
+                .long("adaptive-fps")
+                .help("Lowers the frame rate while capturing can't keep up, instead of producing uneven frame timing")
+        )
+        .arg(
+            Arg::new("capture-priority")
+                .value_parser(clap::value_parser!(i32).range(-20..=19))
+                .allow_negative_numbers(true)
+                .value_name("nice")
+                .required(false)
+                .long("capture-priority")
+                .help("Nice level (-20 highest .. 19 lowest) of the capture thread, for smooth frame timing on busy machines. This is best effort, negative values might need extra permissions")
+        )
+        .arg(
+            Arg::new("list-windows")
+                .num_args(0..=1)
+                .default_missing_value("")
+                .value_name("pattern")
                 .required(false)
                 .short('l')
-                .long("ls-win")
                 .long("ls")
-                .help("If you want to see a list of windows available for recording by their id, you can set env var 'WINDOWID' or `--win-id` to record this specific window only"),
+                .visible_aliases(["ls-win", "list-windows"])
+                .help("If you want to see a list of windows available for recording by their id, you can set env var 'WINDOWID' or `--win-id` to record this specific window only. An optional pattern shows only windows whose name contains it"),
         )
         .arg(
             Arg::new("win-id")
@@ -83,7 +272,43 @@ pub fn launch() -> ArgMatches {
                 .short('w')
                 .long("win-id")
                 .required(false)
-                .help("Window Id (see --ls-win) that should be captured, instead of the current terminal")
+                .help("Window Id (see --ls) that should be captured, instead of the current terminal")
+        )
+        .arg(
+            Arg::new("window")
+                .value_parser(NonEmptyStringValueParser::new())
+                .value_name("title")
+                .long("window")
+                .required(false)
+                .conflicts_with("win-id")
+                .help("Window title (or a part of it, see --ls) that should be captured, instead of the current terminal")
+        )
+        .arg(
+            Arg::new("side-by-side")
+                .value_parser(clap::value_parser!(u64))
+                .action(ArgAction::Append)
+                .value_name("win-id")
+                .long("side-by-side")
+                .required(false)
+                .help("Window Id (see --ls) of another window that should be captured next to the main one, can be given multiple times")
+        )
+        .arg(
+            Arg::new("region")
+                .value_parser(|s: &str| s.parse::<Rect>())
+                .required(false)
+                .long("region")
+                .value_name("x,y,w,h")
+                .help("Captures only this region of the window, in pixels relative to its top left corner, e.g. to record one panel of a large terminal")
+        )
+        .arg(
+            Arg::new("gap")
+                .value_parser(clap::value_parser!(u32))
+                .value_name("px")
+                .default_value("10")
+                .long("gap")
+                .required(false)
+                .requires("side-by-side")
+                .help("Gap in pixels between the windows that are captured side-by-side")
         )
         .arg(
             Arg::new("end-pause")
@@ -103,6 +328,90 @@ pub fn launch() -> ArgMatches {
                 .long("start-pause")
                 .help("to specify the pause time at the start of the animation, that time the gif will show the first frame"),
         )
+        .arg(
+            Arg::new("title")
+                .value_parser(NonEmptyStringValueParser::new())
+                .value_name("text")
+                .required(false)
+                .long("title")
+                .help("Prepends a title card with the given text to the animation"),
+        )
+        .arg(
+            Arg::new("title-pause")
+                .value_parser(NonEmptyStringValueParser::new())
+                .value_name("s | ms | m")
+                .default_value("2s")
+                .required(false)
+                .long("title-pause")
+                .requires("title")
+                .help("to specify how long the title card is shown"),
+        )
+        .arg(
+            Arg::new("end-card")
+                .value_parser(NonEmptyStringValueParser::new())
+                .value_name("text")
+                .required(false)
+                .long("end-card")
+                .help("Appends a closing card with the given text to the animation, e.g. a call to action"),
+        )
+        .arg(
+            Arg::new("end-card-pause")
+                .value_parser(NonEmptyStringValueParser::new())
+                .value_name("s | ms | m")
+                .default_value("4s")
+                .required(false)
+                .long("end-card-pause")
+                .requires("end-card")
+                .help("to specify how long the end card is shown"),
+        )
+        .arg(
+            Arg::new("export-timings")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("export-timings")
+                .help("Exports the timestamp and duration of every frame in the output as csv file next to it, for editing in other tools"),
+        )
+        .arg(
+            Arg::new("frames-dir")
+                .value_parser(NonEmptyStringValueParser::new())
+                .required(false)
+                .long("frames-dir")
+                .value_name("path")
+                .help("Exports the processed frames with a frames.json manifest of their timing into this directory, for own post-processing"),
+        )
+        .arg(
+            Arg::new("estimate")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("estimate")
+                .help("Estimates the size of the output by encoding a sample of the frames, without generating it, e.g. to tune --max-width"),
+        )
+        .arg(
+            Arg::new("open")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("open")
+                .help("Opens the generated gif (or mp4) with the default application when done, skipped in quiet mode"),
+        )
+        .arg(
+            Arg::new("copy")
+                .value_parser(["path", "file"])
+                .num_args(0..=1)
+                .default_missing_value("path")
+                // otherwise the program to launch would be taken as the value, e.g. `--copy vim`
+                .require_equals(true)
+                .required(false)
+                .long("copy")
+                .help("Copies the path of the generated gif (or mp4) to the clipboard when done, with `--copy=file` the file itself is copied. Needs `xclip` on Linux"),
+        )
+        .arg(
+            Arg::new("post-command")
+                .value_parser(NonEmptyStringValueParser::new())
+                .required(false)
+                .long("post-command")
+                .value_name("cmd")
+                .help("Runs a shell command when done, `{}` is replaced by the generated file(s), e.g. `--post-command 'gh gist create {}'`"),
+        )
         .arg(
             Arg::new("file")
                 .value_parser(NonEmptyStringValueParser::new())
@@ -112,6 +421,21 @@ pub fn launch() -> ArgMatches {
                 .default_value("t-rec")
                 .help("to specify the output file (without extension)"),
         )
+        .arg(
+            Arg::new("theme")
+                .value_parser(theme_names())
+                .required(false)
+                .long("theme")
+                .help("Forces a known color theme on the terminal while recording, for consistent colors across machines. Not supported by macOS Terminal.app"),
+        )
+        .arg(
+            Arg::new("output-only")
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .long("output-only")
+                .requires("program")
+                .help("Records only the output of a non-interactive program, it gets no input and typed keys are not echoed. The recording ends with the program"),
+        )
         .arg(
             Arg::new("program")
                 .value_name("shell or program to launch")
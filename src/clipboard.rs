@@ -0,0 +1,94 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::Context;
+
+use crate::Result;
+
+/// what should be put on the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardContent {
+    /// the absolute path of the output file, as text
+    Path,
+    /// the output file itself, so that it can be pasted into other apps
+    File,
+}
+
+/// puts the output file (or its path) on the system clipboard
+pub fn copy_to_clipboard(file: impl AsRef<Path>, content: ClipboardContent) -> Result<()> {
+    let file = file
+        .as_ref()
+        .canonicalize()
+        .with_context(|| format!("Cannot find {:?}", file.as_ref()))?;
+    match content {
+        ClipboardContent::Path => copy_text(&file.to_string_lossy()),
+        ClipboardContent::File => copy_file(&file),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn copy_text(text: &str) -> Result<()> {
+    pipe_into(Command::new("pbcopy"), text)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn copy_text(text: &str) -> Result<()> {
+    let mut cmd = Command::new("xclip");
+    cmd.args(["-selection", "clipboard"]);
+    pipe_into(cmd, text)
+}
+
+/// a file reference on the clipboard can be pasted into most apps, e.g. Finder, Mail or Slack
+#[cfg(target_os = "macos")]
+fn copy_file(file: &Path) -> Result<()> {
+    run(Command::new("osascript").arg("-e").arg(format!(
+        "set the clipboard to (POSIX file {:?})",
+        file.to_string_lossy()
+    )))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn copy_file(file: &Path) -> Result<()> {
+    let mime = match file.extension().and_then(|e| e.to_str()) {
+        Some("gif") => "image/gif",
+        Some("mp4") => "video/mp4",
+        _ => "application/octet-stream",
+    };
+    run(Command::new("xclip")
+        .args(["-selection", "clipboard", "-t", mime, "-i"])
+        .arg(file))
+}
+
+fn pipe_into(mut cmd: Command, text: &str) -> Result<()> {
+    let program = cmd.get_program().to_owned();
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Cannot start {:?} to copy to the clipboard", program))?;
+    child
+        .stdin
+        .take()
+        .context("Cannot write to the clipboard")?
+        .write_all(text.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("{:?} failed to copy to the clipboard", program);
+    }
+
+    Ok(())
+}
+
+fn run(cmd: &mut Command) -> Result<()> {
+    let e = cmd.output().with_context(|| {
+        format!(
+            "Cannot start {:?} to copy to the clipboard",
+            cmd.get_program()
+        )
+    })?;
+    if !e.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&e.stderr))
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::common::Margin;
+use crate::WindowId;
+
+/// what has been learned about a window during calibration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calibration {
+    /// the transparent margins to cut away
+    pub margin: Option<Margin>,
+    /// the window dimensions at the time of calibration
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Calibration {
+    /// a calibration is only valid as long as the window has not been resized
+    pub fn is_valid_for(&self, width: u32, height: u32) -> bool {
+        self.width == width && self.height == height
+    }
+}
+
+/// calibrations per window, so that repeated captures of the same window reuse them
+#[derive(Debug, Default)]
+pub struct CalibrationCache {
+    calibrations: RefCell<HashMap<WindowId, Calibration>>,
+}
+
+impl CalibrationCache {
+    pub fn get(&self, window_id: WindowId) -> Option<Calibration> {
+        self.calibrations.borrow().get(&window_id).cloned()
+    }
+
+    pub fn insert(&self, window_id: WindowId, calibration: Calibration) {
+        self.calibrations
+            .borrow_mut()
+            .insert(window_id, calibration);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_invalidate_calibration_on_resize() {
+        let calibration = Calibration {
+            margin: Some(Margin::new_equal(2)),
+            width: 800,
+            height: 600,
+        };
+
+        assert!(calibration.is_valid_for(800, 600));
+        assert!(!calibration.is_valid_for(801, 600));
+        assert!(!calibration.is_valid_for(800, 599));
+    }
+
+    #[test]
+    fn should_cache_calibrations_per_window() {
+        let cache = CalibrationCache::default();
+        let calibration = Calibration {
+            margin: None,
+            width: 80,
+            height: 24,
+        };
+
+        cache.insert(1, calibration.clone());
+
+        assert_eq!(cache.get(1), Some(calibration));
+        assert_eq!(cache.get(2), None);
+    }
+}
@@ -0,0 +1,97 @@
+use std::convert::TryInto;
+
+/// the ICC profiles to convert captured frames from the colors of their display to sRGB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorProfiles {
+    pub display: Vec<u8>,
+    pub srgb: Vec<u8>,
+}
+
+/// the primaries of an ICC profile, the difference to sRGB is what makes colors look oversaturated
+const PRIMARY_TAGS: [&[u8; 4]; 3] = [b"rXYZ", b"gXYZ", b"bXYZ"];
+/// about the precision of the primaries, as displays store them with slightly different rounding
+const TOLERANCE: f64 = 0.002;
+
+///
+/// true if both ICC profiles have the same primaries, so that converting from one to the other
+/// would not change any color, e.g. for a display that is set to sRGB already.
+/// Profiles that can't be read are never the same.
+#[allow(dead_code)] // only macOS reads the profile of a display
+pub fn have_same_primaries(a: &[u8], b: &[u8]) -> bool {
+    PRIMARY_TAGS
+        .iter()
+        .all(|tag| match (xyz_tag(a, tag), xyz_tag(b, tag)) {
+            (Some(a), Some(b)) => a
+                .iter()
+                .zip(b.iter())
+                .all(|(a, b)| (a - b).abs() < TOLERANCE),
+            _ => false,
+        })
+}
+
+/// reads a XYZ tag of an ICC profile, see the ICC specification, section 7.3 and 10.31
+fn xyz_tag(profile: &[u8], signature: &[u8; 4]) -> Option<[f64; 3]> {
+    let u32_at = |i: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(profile.get(i..i + 4)?.try_into().ok()?))
+    };
+    let tag_count = u32_at(128)? as usize;
+    let entry = (0..tag_count)
+        .map(|i| 132 + i * 12)
+        .find(|&entry| profile.get(entry..entry + 4) == Some(&signature[..]))?;
+    let offset = u32_at(entry + 4)? as usize;
+    if profile.get(offset..offset + 4) != Some(&b"XYZ "[..]) {
+        return None;
+    }
+    let value = |i: usize| Some(u32_at(offset + 8 + i * 4)? as i32 as f64 / 65536.0);
+
+    Some([value(0)?, value(1)?, value(2)?])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// a minimal ICC profile, with nothing but the primaries
+    fn profile(primaries: [[f64; 3]; 3]) -> Vec<u8> {
+        let mut profile = vec![0; 128];
+        profile.extend(3_u32.to_be_bytes());
+        let data_start = 132 + 3 * 12;
+        for (i, tag) in PRIMARY_TAGS.iter().enumerate() {
+            profile.extend(tag.iter());
+            profile.extend(((data_start + i * 20) as u32).to_be_bytes());
+            profile.extend(20_u32.to_be_bytes());
+        }
+        for xyz in primaries {
+            profile.extend(b"XYZ \0\0\0\0");
+            for v in xyz {
+                profile.extend(((v * 65536.0).round() as i32).to_be_bytes());
+            }
+        }
+        profile
+    }
+
+    const SRGB: [[f64; 3]; 3] = [
+        [0.4361, 0.2225, 0.0139],
+        [0.3851, 0.7169, 0.0971],
+        [0.1431, 0.0606, 0.7141],
+    ];
+    const DISPLAY_P3: [[f64; 3]; 3] = [
+        [0.5151, 0.2412, -0.0011],
+        [0.2920, 0.6922, 0.0419],
+        [0.1571, 0.0666, 0.7841],
+    ];
+
+    #[test]
+    fn should_find_same_primaries() {
+        let mut almost_srgb = SRGB;
+        almost_srgb[0][0] += 0.0005;
+
+        assert!(have_same_primaries(&profile(SRGB), &profile(almost_srgb)));
+    }
+
+    #[test]
+    fn should_find_different_primaries() {
+        assert!(!have_same_primaries(&profile(DISPLAY_P3), &profile(SRGB)));
+        assert!(!have_same_primaries(&[], &profile(SRGB)));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{Image, Result};
+use image::flat::View;
+use image::{GenericImageView, Rgba};
+use std::collections::HashMap;
+
+///
+/// this helps to pick a background color that blends in with the recorded window
+/// it samples the outer edge pixels of a (calibrated) frame and returns the dominant color,
+/// or `None` if the edges are too colorful or too transparent to be sure
+pub fn identify_background_color(image: Image) -> Result<Option<Rgba<u8>>> {
+    let image: View<_, Rgba<u8>> = image.as_view()?;
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return Ok(None);
+    }
+
+    let mut edge = Vec::with_capacity(2 * (width + height) as usize);
+    for x in 0..width {
+        edge.push(image.get_pixel(x, 0));
+        edge.push(image.get_pixel(x, height - 1));
+    }
+    for y in 1..height.saturating_sub(1) {
+        edge.push(image.get_pixel(0, y));
+        edge.push(image.get_pixel(width - 1, y));
+    }
+
+    let mut histogram: HashMap<Rgba<u8>, usize> = HashMap::new();
+    for pixel in edge.iter() {
+        *histogram.entry(*pixel).or_default() += 1;
+    }
+    let dominant = histogram
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .filter(|(Rgba([_, _, _, a]), _)| *a == 0xff);
+
+    // more than half of the edge must agree, otherwise it's just guessing
+    Ok(dominant
+        .filter(|(_, count)| count * 2 > edge.len())
+        .map(|(color, _)| color))
+}
+
+/// formats a color the way `convert` understands it, e.g. `#1e1e1e`
+pub fn as_hex_color(color: &Rgba<u8>) -> String {
+    let Rgba([r, g, b, _]) = color;
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::RgbaImage;
+
+    #[test]
+    fn should_identify_dominant_edge_color() -> Result<()> {
+        // given a dark terminal with a bit of colored text in the middle
+        let mut image = RgbaImage::from_pixel(20, 10, Rgba([30, 30, 30, 0xff]));
+        image.put_pixel(10, 5, Rgba([0xff, 0, 0, 0xff]));
+        image.put_pixel(0, 0, Rgba([0, 0xff, 0, 0xff]));
+
+        // when
+        let color = identify_background_color(image.into_flat_samples())?;
+
+        // then
+        assert_eq!(color, Some(Rgba([30, 30, 30, 0xff])));
+        assert_eq!(as_hex_color(&color.unwrap()), "#1e1e1e");
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_identify_ambiguous_edge_color() -> Result<()> {
+        // given a frame where every other pixel has a different color
+        let image = RgbaImage::from_fn(20, 10, |x, _| {
+            if x % 2 == 0 {
+                Rgba([0, 0, 0, 0xff])
+            } else {
+                Rgba([0xff, 0xff, 0xff, 0xff])
+            }
+        });
+
+        // when
+        let color = identify_background_color(image.into_flat_samples())?;
+
+        // then
+        assert_eq!(color, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_identify_transparent_edge_color() -> Result<()> {
+        let image = RgbaImage::from_pixel(20, 10, Rgba([0, 0, 0, 0]));
+
+        let color = identify_background_color(image.into_flat_samples())?;
+
+        assert_eq!(color, None);
+
+        Ok(())
+    }
+}
@@ -1,10 +1,51 @@
-use crate::common::Margin;
+use crate::common::{Margin, Rect};
 use crate::{Image, ImageOnHeap, Result};
-use image::flat::View;
-use image::{imageops, GenericImageView, ImageBuffer, Rgba};
+use image::flat::{SampleLayout, View};
+use image::imageops::FilterType;
+use image::{imageops, ColorType, FlatSamples, GenericImageView, ImageBuffer, Rgba, RgbaImage};
+
+/// the order of the color channels in a raw pixel buffer, as delivered by a platform api
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOrder {
+    #[allow(dead_code)] // none of the current platforms delivers RGBA natively
+    Rgba,
+    Bgra,
+}
+
+/// how the color channels of a raw pixel buffer relate to its alpha channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphaMode {
+    Straight,
+    /// the colors are already multiplied with the alpha, as CoreGraphics and X11 ARGB visuals deliver it
+    Premultiplied,
+}
+
+/// turns a raw pixel buffer (4 bytes per pixel) into an [`Image`] with the canonical RGBA channel order
+/// and straight alpha. All the post-processing (and blending) relies on that,
+/// so this is the only place where channels get swapped and alpha gets normalized.
+/// Otherwise semi-transparent pixels would end up too dark, e.g. as dark fringes at rounded corners.
+pub fn from_raw_pixels(
+    mut raw_data: Vec<u8>,
+    order: ChannelOrder,
+    alpha: AlphaMode,
+    width: u32,
+    height: u32,
+) -> Image {
+    if order == ChannelOrder::Bgra {
+        convert_bgra_to_rgba(&mut raw_data);
+    }
+    if alpha == AlphaMode::Premultiplied {
+        unpremultiply_alpha(&mut raw_data);
+    }
+
+    FlatSamples {
+        samples: raw_data,
+        layout: SampleLayout::row_major_packed(4, width, height),
+        color_hint: Some(ColorType::Rgba8),
+    }
+}
 
 /// specialized version of crop for [`ImageOnHeap`] and [`Margin`]
-#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
 pub fn crop(image: Image, margin: &Margin) -> Result<ImageOnHeap> {
     let mut img2: View<_, Rgba<u8>> = image.as_view()?;
     let (width, height) = (
@@ -29,17 +70,320 @@ pub fn crop(image: Image, margin: &Margin) -> Result<ImageOnHeap> {
     Ok(Box::new(buf.into_flat_samples()))
 }
 
-pub fn convert_bgra_to_rgba(buffer: &mut [u8]) {
+/// crops a region out of an image, the region is relative to the top left corner of the image
+/// and must not exceed it
+pub fn crop_region(image: Image, region: &Rect) -> Result<ImageOnHeap> {
+    let (width, height) = (image.layout.width as i64, image.layout.height as i64);
+    let (right, bottom) = (
+        region.x as i64 + region.width as i64,
+        region.y as i64 + region.height as i64,
+    );
+    if region.is_empty() || region.x < 0 || region.y < 0 || right > width || bottom > height {
+        anyhow::bail!(
+            "The region {},{},{},{} exceeds the captured frame of {}x{}",
+            region.x,
+            region.y,
+            region.width,
+            region.height,
+            width,
+            height
+        );
+    }
+    let margin = Margin::new(
+        region.y as u16,
+        (width - right) as u16,
+        (height - bottom) as u16,
+        region.x as u16,
+    );
+
+    crop(image, &margin)
+}
+
+/// stitches images side-by-side from left to right, aligned at the top, with a transparent gap in between
+pub fn stitch_horizontally(images: &[ImageOnHeap], gap: u32) -> Result<ImageOnHeap> {
+    let views = images
+        .iter()
+        .map(|image| image.as_view::<Rgba<u8>>())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let width = views.iter().map(|view| view.width()).sum::<u32>()
+        + gap * (views.len() as u32).saturating_sub(1);
+    let height = views.iter().map(|view| view.height()).max().unwrap_or(0);
+
+    let mut canvas = ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 0]));
+    let mut x = 0;
+    for view in views.iter() {
+        imageops::replace(&mut canvas, view, x, 0);
+        x += (view.width() + gap) as i64;
+    }
+
+    Ok(Box::new(canvas.into_flat_samples()))
+}
+
+/// the axis a frame is mirrored at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flip {
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+/// mirrors an image, `Both` is the same as rotating by 180°
+pub fn flip(image: &RgbaImage, flip: Flip) -> RgbaImage {
+    match flip {
+        Flip::Horizontal => imageops::flip_horizontal(image),
+        Flip::Vertical => imageops::flip_vertical(image),
+        Flip::Both => imageops::rotate180(image),
+    }
+}
+
+/// clockwise rotation of a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+/// rotates an image clockwise, for 90° and 270° width and height are swapped
+pub fn rotate(image: &RgbaImage, rotation: Rotation) -> RgbaImage {
+    match rotation {
+        Rotation::Rotate90 => imageops::rotate90(image),
+        Rotation::Rotate180 => imageops::rotate180(image),
+        Rotation::Rotate270 => imageops::rotate270(image),
+    }
+}
+
+/// downscales an image to fit into the given maximum width and / or height, preserving the aspect ratio.
+/// Images that already fit are returned untouched, they are never upscaled.
+pub fn downscale(
+    image: RgbaImage,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+    filter: FilterType,
+) -> RgbaImage {
+    match fit_within(image.width(), image.height(), max_width, max_height) {
+        Some((width, height)) => scale(&image, width, height, filter),
+        None => image,
+    }
+}
+
+/// all scaling goes through here, so that the resampling filter is honored everywhere
+pub fn scale(image: &RgbaImage, width: u32, height: u32, filter: FilterType) -> RgbaImage {
+    imageops::resize(image, width, height, filter)
+}
+
+/// the dimensions that fit into the maximum width and / or height, `None` if it already fits
+fn fit_within(
+    width: u32,
+    height: u32,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+) -> Option<(u32, u32)> {
+    let scale_x = max_width.map_or(1.0, |max| max as f64 / width as f64);
+    let scale_y = max_height.map_or(1.0, |max| max as f64 / height as f64);
+    let scale = scale_x.min(scale_y);
+    if scale >= 1.0 {
+        return None;
+    }
+
+    Some((
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    ))
+}
+
+fn convert_bgra_to_rgba(buffer: &mut [u8]) {
     for chunk in buffer.chunks_exact_mut(4) {
         chunk.swap(0, 2);
     }
 }
 
+/// turns premultiplied RGBA into straight RGBA, fully transparent pixels are left as they are
+fn unpremultiply_alpha(buffer: &mut [u8]) {
+    for pixel in buffer.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 || alpha == 0xff {
+            continue;
+        }
+        for channel in pixel[..3].iter_mut() {
+            *channel = ((*channel as u32 * 0xff + alpha / 2) / alpha).min(0xff) as u8;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use image::open;
 
+    #[test]
+    fn should_convert_bgra_pixels_to_rgba() -> Result<()> {
+        // given a red, a green and a blue pixel in BGRA order
+        let raw = vec![0, 0, 0xff, 0xff, 0, 0xff, 0, 0xff, 0xff, 0, 0, 0x80];
+
+        // when
+        let image = from_raw_pixels(raw, ChannelOrder::Bgra, AlphaMode::Straight, 3, 1);
+
+        // then
+        let view: View<_, Rgba<u8>> = image.as_view()?;
+        assert_eq!(image.color_hint, Some(ColorType::Rgba8));
+        assert_eq!(view.get_pixel(0, 0), Rgba([0xff, 0, 0, 0xff]));
+        assert_eq!(view.get_pixel(1, 0), Rgba([0, 0xff, 0, 0xff]));
+        assert_eq!(view.get_pixel(2, 0), Rgba([0, 0, 0xff, 0x80]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_rgba_pixels() -> Result<()> {
+        let raw = vec![0xff, 0, 0, 0xff];
+
+        let image = from_raw_pixels(raw, ChannelOrder::Rgba, AlphaMode::Straight, 1, 1);
+
+        let view: View<_, Rgba<u8>> = image.as_view()?;
+        assert_eq!(view.get_pixel(0, 0), Rgba([0xff, 0, 0, 0xff]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_turn_premultiplied_into_straight_alpha() -> Result<()> {
+        // given a half transparent red, an opaque and a fully transparent pixel, premultiplied
+        let raw = vec![0x80, 0, 0, 0x80, 0x40, 0x40, 0x40, 0xff, 0x10, 0, 0, 0];
+
+        // when
+        let image = from_raw_pixels(raw, ChannelOrder::Rgba, AlphaMode::Premultiplied, 3, 1);
+
+        // then
+        let view: View<_, Rgba<u8>> = image.as_view()?;
+        assert_eq!(view.get_pixel(0, 0), Rgba([0xff, 0, 0, 0x80]));
+        assert_eq!(view.get_pixel(1, 0), Rgba([0x40, 0x40, 0x40, 0xff]));
+        assert_eq!(view.get_pixel(2, 0), Rgba([0x10, 0, 0, 0]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_blend_semi_transparent_pixel_without_dark_fringe() -> Result<()> {
+        // given a half transparent red pixel, as delivered premultiplied, over a white background
+        let raw = vec![0x80, 0, 0, 0x80];
+        let image = from_raw_pixels(raw, ChannelOrder::Rgba, AlphaMode::Premultiplied, 1, 1);
+        let view: View<_, Rgba<u8>> = image.as_view()?;
+        let mut background = RgbaImage::from_pixel(1, 1, Rgba([0xff, 0xff, 0xff, 0xff]));
+
+        // when
+        imageops::overlay(&mut background, &view, 0, 0);
+
+        // then red is fully saturated and the other channels are half way to white
+        let Rgba([r, g, b, a]) = *background.get_pixel(0, 0);
+        assert_eq!(r, 0xff);
+        assert!(a >= 0xfe, "alpha was {}", a);
+        assert!((0x7e..=0x80).contains(&g), "green was {}", g);
+        assert!((0x7e..=0x80).contains(&b), "blue was {}", b);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_stitch_images_side_by_side() -> Result<()> {
+        // given a red 2x2 and a blue 3x1 image
+        let red = ImageBuffer::from_pixel(2, 2, Rgba([0xff, 0, 0, 0xff]));
+        let blue = ImageBuffer::from_pixel(3, 1, Rgba([0, 0, 0xff, 0xff]));
+        let images = vec![
+            ImageOnHeap::new(red.into_flat_samples()),
+            ImageOnHeap::new(blue.into_flat_samples()),
+        ];
+
+        // when
+        let stitched = stitch_horizontally(&images, 1)?;
+
+        // then
+        let view: View<_, Rgba<u8>> = stitched.as_view()?;
+        assert_eq!(view.dimensions(), (6, 2));
+        assert_eq!(view.get_pixel(1, 1), Rgba([0xff, 0, 0, 0xff]));
+        assert_eq!(view.get_pixel(2, 0), Rgba([0, 0, 0, 0]));
+        assert_eq!(view.get_pixel(3, 0), Rgba([0, 0, 0xff, 0xff]));
+        assert_eq!(view.get_pixel(5, 1), Rgba([0, 0, 0, 0]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_flip() {
+        // given a 2x2 image with a single red pixel top left
+        let mut image = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 0xff]));
+        image.put_pixel(0, 0, Rgba([0xff, 0, 0, 0xff]));
+        let red = Rgba([0xff, 0, 0, 0xff]);
+
+        // when / then
+        assert_eq!(flip(&image, Flip::Horizontal).get_pixel(1, 0), &red);
+        assert_eq!(flip(&image, Flip::Vertical).get_pixel(0, 1), &red);
+        assert_eq!(flip(&image, Flip::Both).get_pixel(1, 1), &red);
+    }
+
+    #[test]
+    fn should_rotate() {
+        // given a 3x2 image with a single red pixel top left
+        let mut image = RgbaImage::from_pixel(3, 2, Rgba([0, 0, 0, 0xff]));
+        image.put_pixel(0, 0, Rgba([0xff, 0, 0, 0xff]));
+        let red = Rgba([0xff, 0, 0, 0xff]);
+
+        // when
+        let rotated_90 = rotate(&image, Rotation::Rotate90);
+        let rotated_180 = rotate(&image, Rotation::Rotate180);
+        let rotated_270 = rotate(&image, Rotation::Rotate270);
+
+        // then
+        assert_eq!(rotated_90.dimensions(), (2, 3));
+        assert_eq!(rotated_90.get_pixel(1, 0), &red);
+        assert_eq!(rotated_180.dimensions(), (3, 2));
+        assert_eq!(rotated_180.get_pixel(2, 1), &red);
+        assert_eq!(rotated_270.dimensions(), (2, 3));
+        assert_eq!(rotated_270.get_pixel(0, 2), &red);
+    }
+
+    #[test]
+    fn should_fit_within_preserving_aspect_ratio() {
+        assert_eq!(fit_within(1000, 500, Some(400), None), Some((400, 200)));
+        assert_eq!(fit_within(1000, 500, None, Some(100)), Some((200, 100)));
+        assert_eq!(
+            fit_within(1000, 500, Some(800), Some(300)),
+            Some((600, 300))
+        );
+        assert_eq!(fit_within(1000, 500, Some(1000), Some(800)), None);
+        assert_eq!(fit_within(1000, 500, None, None), None);
+    }
+
+    #[test]
+    fn should_downscale() {
+        let image = RgbaImage::from_pixel(40, 30, Rgba([30, 30, 30, 0xff]));
+
+        let scaled = downscale(image, Some(20), None, FilterType::Lanczos3);
+
+        assert_eq!(scaled.dimensions(), (20, 15));
+        assert_eq!(scaled.get_pixel(10, 7), &Rgba([30, 30, 30, 0xff]));
+    }
+
+    #[test]
+    fn should_keep_hard_edges_with_nearest_filter() {
+        // given a black and white checkerboard of 2x2 blocks
+        let image = RgbaImage::from_fn(8, 8, |x, y| {
+            if (x / 2 + y / 2) % 2 == 0 {
+                Rgba([0, 0, 0, 0xff])
+            } else {
+                Rgba([0xff, 0xff, 0xff, 0xff])
+            }
+        });
+
+        // when
+        let scaled = scale(&image, 4, 4, FilterType::Nearest);
+
+        // then no gray pixels in between
+        assert!(scaled
+            .pixels()
+            .all(|p| *p == Rgba([0, 0, 0, 0xff]) || *p == Rgba([0xff, 0xff, 0xff, 0xff])));
+    }
+
     #[test]
     fn should_crop() -> Result<()> {
         // given
@@ -57,4 +401,28 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn should_crop_region() -> Result<()> {
+        // given
+        let image = RgbaImage::from_fn(10, 8, |x, y| Rgba([x as u8, y as u8, 0, 0xff]));
+
+        // when
+        let cropped = crop_region(image.into_flat_samples(), &Rect::new(2, 3, 4, 5))?;
+
+        // then
+        assert_eq!((cropped.layout.width, cropped.layout.height), (4, 5));
+        assert_eq!(&cropped.samples[..4], &[2, 3, 0, 0xff]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_crop_region_exceeding_the_frame() {
+        let image = RgbaImage::new(10, 8);
+
+        let result = crop_region(image.into_flat_samples(), &Rect::new(2, 3, 9, 5));
+
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,76 @@
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+
+use image::{Rgba, RgbaImage};
+
+use crate::common::{PlatformApi, Rect};
+use crate::{ImageOnHeap, Result, WindowId, WindowList};
+
+/// a test double that serves canned frames and a fixed window list,
+/// so that everything above the platform apis can be tested on any OS without a display
+pub struct MockPlatformApi {
+    frames: Mutex<Vec<ImageOnHeap>>,
+    windows: WindowList,
+    /// signaled once the last canned frame was served, e.g. to stop a capture
+    stop: Option<Mutex<Sender<()>>>,
+}
+
+impl MockPlatformApi {
+    /// serves the given frames in order, once exhausted the last frame is repeated
+    pub fn new(frames: Vec<ImageOnHeap>) -> Self {
+        let mut frames = frames;
+        frames.reverse();
+        Self {
+            frames: Mutex::new(frames),
+            windows: vec![(Some("mock".to_string()), 1)],
+            stop: None,
+        }
+    }
+
+    /// sends a stop signal once the last canned frame was served
+    pub fn stop_when_exhausted(mut self, stop: Sender<()>) -> Self {
+        self.stop = Some(Mutex::new(stop));
+        self
+    }
+
+    /// a solid colored frame, different colors make different frames
+    pub fn frame(width: u32, height: u32, color: [u8; 4]) -> ImageOnHeap {
+        Box::new(RgbaImage::from_pixel(width, height, Rgba(color)).into_flat_samples())
+    }
+}
+
+impl PlatformApi for MockPlatformApi {
+    fn calibrate(&mut self, _window_id: WindowId) -> Result<()> {
+        Ok(())
+    }
+
+    fn window_list(&self) -> Result<WindowList> {
+        Ok(self.windows.clone())
+    }
+
+    fn capture_window_screenshot(&self, _window_id: WindowId) -> Result<ImageOnHeap> {
+        let mut frames = self.frames.lock().unwrap();
+        if frames.len() > 1 {
+            return Ok(frames.pop().unwrap());
+        }
+        if let Some(stop) = self.stop.as_ref() {
+            stop.lock().unwrap().send(()).ok();
+        }
+        frames
+            .last()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No frames to serve"))
+    }
+
+    fn get_active_window(&self) -> Result<WindowId> {
+        Ok(self.windows[0].1)
+    }
+
+    fn window_bounds(&self, _window_id: WindowId) -> Result<Option<Rect>> {
+        Ok(Some(Rect::new(0, 0, 100, 100)))
+    }
+
+    fn screen_bounds(&self) -> Result<Vec<Rect>> {
+        Ok(vec![Rect::new(0, 0, 1920, 1080)])
+    }
+}
@@ -1,8 +1,23 @@
+mod calibration;
+mod color_profile;
+pub mod identify_background;
 pub mod identify_transparency;
 pub mod image;
 mod margin;
+#[cfg(test)]
+mod mock_platform_api;
 mod platform_api;
+mod rect;
+#[cfg(unix)]
+pub mod signal;
+#[cfg(unix)]
+pub mod tty;
 pub mod utils;
 
+pub use calibration::*;
+pub use color_profile::*;
 pub use margin::*;
+#[cfg(test)]
+pub use mock_platform_api::*;
 pub use platform_api::*;
+pub use rect::*;
@@ -1,11 +1,17 @@
+use crate::common::Rect;
 use crate::{ImageOnHeap, Result, WindowId, WindowList};
 
 pub trait PlatformApi: Send {
     /// 1. it does check for the screenshot
     /// 2. it checks for transparent margins and configures the api
-    ///     to cut them away in further screenshots
+    ///    to cut them away in further screenshots
     fn calibrate(&mut self, window_id: WindowId) -> Result<()>;
     fn window_list(&self) -> Result<WindowList>;
     fn capture_window_screenshot(&self, window_id: WindowId) -> Result<ImageOnHeap>;
     fn get_active_window(&self) -> Result<WindowId>;
+    /// the bounds of the window in screen coordinates,
+    /// `None` if the window is not on screen, e.g. minimized
+    fn window_bounds(&self, window_id: WindowId) -> Result<Option<Rect>>;
+    /// the bounds of all screens
+    fn screen_bounds(&self) -> Result<Vec<Rect>>;
 }
@@ -0,0 +1,101 @@
+use std::str::FromStr;
+
+/// a rectangle in screen coordinates, e.g. the bounds of a window or a screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// true if both rectangles share at least one pixel
+    pub fn intersects(&self, other: &Rect) -> bool {
+        let (left, right) = (
+            self.x.max(other.x) as i64,
+            (self.x as i64 + self.width as i64).min(other.x as i64 + other.width as i64),
+        );
+        let (top, bottom) = (
+            self.y.max(other.y) as i64,
+            (self.y as i64 + self.height as i64).min(other.y as i64 + other.height as i64),
+        );
+
+        left < right && top < bottom
+    }
+}
+
+/// parses a rectangle like `x,y,width,height`, e.g. `0,0,800,600`
+impl FromStr for Rect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' is not a valid region, expected x,y,width,height", s);
+        let parts = s.split(',').map(str::trim).collect::<Vec<_>>();
+        if let [x, y, width, height] = parts[..] {
+            let rect = Rect::new(
+                x.parse().map_err(|_| invalid())?,
+                y.parse().map_err(|_| invalid())?,
+                width.parse().map_err(|_| invalid())?,
+                height.parse().map_err(|_| invalid())?,
+            );
+            if rect.is_empty() {
+                return Err(invalid());
+            }
+            Ok(rect)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rect_from_str() {
+        assert_eq!("10,20,800,600".parse(), Ok(Rect::new(10, 20, 800, 600)));
+        assert_eq!("10, 20, 800, 600".parse(), Ok(Rect::new(10, 20, 800, 600)));
+        assert!("10,20,800".parse::<Rect>().is_err());
+        assert!("10,20,0,600".parse::<Rect>().is_err());
+        assert!("a,20,800,600".parse::<Rect>().is_err());
+    }
+
+    #[test]
+    fn rect_intersects() {
+        let screen = Rect::new(0, 0, 1920, 1080);
+        assert!(Rect::new(100, 100, 800, 600).intersects(&screen));
+        assert!(Rect::new(-100, -100, 200, 200).intersects(&screen));
+        assert!(Rect::new(1900, 1000, 800, 600).intersects(&screen));
+    }
+
+    #[test]
+    fn rect_does_not_intersect() {
+        let screen = Rect::new(0, 0, 1920, 1080);
+        assert!(!Rect::new(1920, 0, 800, 600).intersects(&screen));
+        assert!(!Rect::new(-800, 0, 800, 600).intersects(&screen));
+        assert!(!Rect::new(0, -32000, 800, 600).intersects(&screen));
+        assert!(!Rect::new(100, 100, 0, 600).intersects(&screen));
+    }
+
+    #[test]
+    fn rect_is_empty() {
+        assert!(Rect::new(0, 0, 0, 10).is_empty());
+        assert!(Rect::new(0, 0, 10, 0).is_empty());
+        assert!(!Rect::new(0, 0, 10, 10).is_empty());
+    }
+}
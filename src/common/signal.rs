@@ -0,0 +1,24 @@
+//! t-rec shares the terminal with the recorded program, so a Ctrl+C reaches both of them.
+//! While recording, t-rec must survive it, so that the frames captured so far are not lost.
+
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+    // nothing to do, the recorded program gets the signal too and decides what to do
+}
+
+/// keeps t-rec alive on Ctrl+C while recording.
+/// NOTE: a handler is needed instead of `SIG_IGN`, because ignored signals would be inherited by the sub shell
+pub fn catch_interrupt() {
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+/// restores the default Ctrl+C behavior, so that generating the output can be aborted
+pub fn release_interrupt() {
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+    }
+}
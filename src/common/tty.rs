@@ -0,0 +1,25 @@
+//! With `--output-only` the terminal should show only what the recorded program prints,
+//! so the echo of typed keys is turned off while recording.
+
+/// turns off the echo of typed keys, returns the previous settings to restore them later,
+/// or `None` if stdin is not a terminal
+pub fn disable_echo() -> Option<libc::termios> {
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+            return None;
+        }
+        let previous = termios;
+        termios.c_lflag &= !libc::ECHO;
+        libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+
+        Some(previous)
+    }
+}
+
+/// restores the terminal settings from before [`disable_echo`]
+pub fn restore_echo(previous: libc::termios) {
+    unsafe {
+        libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &previous);
+    }
+}
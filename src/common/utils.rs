@@ -1,5 +1,6 @@
 use anyhow::Context;
 use humantime::{format_duration, parse_duration};
+use std::io::Write;
 use std::ops::Add;
 use std::time::Duration;
 
@@ -33,6 +34,17 @@ pub fn clear_screen() {
     print!("{esc}[H", esc = 27 as char);
 }
 
+/// counts down the seconds on the terminal, so that one can get ready before the recording starts
+pub fn countdown(secs: u64) {
+    for remaining in (1..=secs).rev() {
+        print!("\r[t-rec]: Recording starts in {} ", remaining);
+        std::io::stdout().flush().ok();
+        std::thread::sleep(ONE_SEC);
+    }
+    clear_screen();
+    std::io::stdout().flush().ok();
+}
+
 /// parses a human duration string into something valid
 pub fn parse_delay(
     s: Option<impl AsRef<str>>,
@@ -1,10 +1,15 @@
+use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
 
 use anyhow::Context;
+use image::imageops::FilterType;
+use image::RgbaImage;
 use rayon::prelude::*;
 use tempfile::TempDir;
 
+use crate::common::image::{downscale, flip, rotate, Flip, Rotation};
+use crate::common::ColorProfiles;
 use crate::utils::IMG_EXT;
 use crate::Result;
 
@@ -92,6 +97,99 @@ pub fn apply_big_sur_corner_effect(time_codes: &[u128], tempdir: &TempDir) {
     )
 }
 
+///
+/// normalizes the colors of all frames from the display color profile to sRGB,
+/// so that they look the same in viewers on other machines.
+/// Since the frames do not carry any profile, the first `-profile` assigns the display profile
+/// and the second one converts to sRGB. Both profiles are written next to the frames
+///
+/// ```sh
+/// convert t-rec-frame-000000251.tga \
+///     -profile t-rec-display.icc \
+///     -profile t-rec-srgb.icc \
+///     t-rec-frame-000000251.tga
+/// ```
+pub fn apply_srgb_normalization(
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    profiles: &ColorProfiles,
+) -> Result<()> {
+    let display_profile = tempdir.path().join("t-rec-display.icc");
+    let srgb_profile = tempdir.path().join("t-rec-srgb.icc");
+    fs::write(&display_profile, &profiles.display).context("Cannot write the display profile")?;
+    fs::write(&srgb_profile, &profiles.srgb).context("Cannot write the sRGB profile")?;
+
+    apply_effect(
+        time_codes,
+        tempdir,
+        Box::new(move |file| {
+            let e = Command::new("convert")
+                .arg(file.to_str().unwrap())
+                .arg("-profile")
+                .arg(&display_profile)
+                .arg("-profile")
+                .arg(&srgb_profile)
+                .arg(file.to_str().unwrap())
+                .output()
+                .context("Cannot apply sRGB color normalization")?;
+
+            if !e.status.success() {
+                anyhow::bail!("{}", String::from_utf8_lossy(&e.stderr))
+            } else {
+                Ok(())
+            }
+        }),
+    );
+    Ok(())
+}
+
+///
+/// mirrors all frames, this is done in-process since it is a plain pixel transform
+pub fn apply_flip(time_codes: &[u128], tempdir: &TempDir, direction: Flip) {
+    apply_image_effect(time_codes, tempdir, move |image| flip(&image, direction))
+}
+
+///
+/// rotates all frames clockwise, for 90° and 270° the frames become portrait (or landscape)
+pub fn apply_rotation(time_codes: &[u128], tempdir: &TempDir, rotation: Rotation) {
+    apply_image_effect(time_codes, tempdir, move |image| rotate(&image, rotation))
+}
+
+///
+/// downscales all frames that exceed the maximum width and / or height, preserving the aspect ratio
+pub fn apply_downscale(
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+    filter: FilterType,
+) {
+    apply_image_effect(time_codes, tempdir, move |image| {
+        downscale(image, max_width, max_height, filter)
+    })
+}
+
+///
+/// apply a given transformation of the decoded frame to all frames
+fn apply_image_effect(
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    transform: impl Fn(RgbaImage) -> RgbaImage + Send + Sync + 'static,
+) {
+    apply_effect(
+        time_codes,
+        tempdir,
+        Box::new(move |file| {
+            let image = image::open(&file)
+                .with_context(|| format!("Cannot open frame {:?}", file))?
+                .into_rgba8();
+            transform(image)
+                .save(&file)
+                .with_context(|| format!("Cannot save frame {:?}", file))
+        }),
+    )
+}
+
 ///
 /// apply a given effect (closure) to all frames
 ///
@@ -0,0 +1,123 @@
+use std::env;
+
+use crate::generators::OutputFormat;
+use crate::{get_window_id_for, setup, PlatformApi, Result};
+
+/// the outcome of a single check, with a hint how to fix it if it is not passing
+enum Check {
+    Pass(String),
+    Warn(String, String),
+    Fail(String, String),
+}
+
+///
+/// checks everything t-rec needs, prints a report and fails if anything essential is missing
+pub fn doctor() -> Result<()> {
+    let mut checks = vec![];
+    match setup() {
+        Ok(api) => {
+            checks.push(Check::Pass(format!(
+                "platform api is available ({})",
+                env::consts::OS
+            )));
+            checks.push(check_screens(&api));
+            checks.push(check_window(&api));
+        }
+        Err(e) => checks.push(Check::Fail(
+            format!("platform api is not available: {}", e),
+            PLATFORM_HINT.to_string(),
+        )),
+    }
+    for format in OutputFormat::ALL.iter() {
+        checks.push(check_format(*format));
+    }
+
+    let mut failed = 0;
+    for check in checks.iter() {
+        match check {
+            Check::Pass(msg) => println!("✅ {}", msg),
+            Check::Warn(msg, hint) => println!("⚠️ {}\n   ↳ {}", msg, hint),
+            Check::Fail(msg, hint) => {
+                failed += 1;
+                println!("❌ {}\n   ↳ {}", msg, hint)
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed", failed);
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+const PLATFORM_HINT: &str =
+    "Grant your terminal the 'Screen Recording' permission in System Settings > Privacy & Security";
+#[cfg(not(target_os = "macos"))]
+const PLATFORM_HINT: &str = "t-rec needs a running X11 server, make sure `DISPLAY` is set";
+
+fn check_screens(api: &impl PlatformApi) -> Check {
+    match api.screen_bounds() {
+        Ok(screens) if !screens.is_empty() => Check::Pass(format!(
+            "{} screen(s) detected: {}",
+            screens.len(),
+            screens
+                .iter()
+                .map(|s| format!("{}x{}", s.width, s.height))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Ok(_) => Check::Warn(
+            "no screen detected".to_string(),
+            "windows cannot be checked for being on screen before recording".to_string(),
+        ),
+        Err(e) => Check::Warn(
+            format!("screens cannot be detected: {}", e),
+            "windows cannot be checked for being on screen before recording".to_string(),
+        ),
+    }
+}
+
+fn check_window(api: &impl PlatformApi) -> Check {
+    if let Ok(win_id) = env::var("WINDOWID") {
+        return Check::Pass(format!("terminal window is given by WINDOWID={}", win_id));
+    }
+    match env::var("TERM_PROGRAM") {
+        Ok(terminal) => match get_window_id_for(terminal.clone()) {
+            Ok((win_id, _)) => Check::Pass(format!(
+                "terminal window {} found by TERM_PROGRAM={}",
+                win_id, terminal
+            )),
+            Err(_) => Check::Fail(
+                format!("no window found for TERM_PROGRAM={}", terminal),
+                "set WINDOWID or pass `--win-id`, see `t-rec --ls` for all windows".to_string(),
+            ),
+        },
+        Err(_) => Check::Warn(
+            format!(
+                "neither WINDOWID nor TERM_PROGRAM is set, the active window is recorded{}",
+                api.get_active_window()
+                    .map(|win_id| format!(" (currently {})", win_id))
+                    .unwrap_or_default()
+            ),
+            "set WINDOWID or pass `--win-id` to record a specific window".to_string(),
+        ),
+    }
+}
+
+fn check_format(format: OutputFormat) -> Check {
+    match (format.check(), format) {
+        (Ok(_), _) => Check::Pass(format!(
+            "{} can be generated with {}",
+            format,
+            format.tool_version().unwrap_or_default()
+        )),
+        (Err(e), OutputFormat::Gif) => {
+            Check::Fail(format!("{} cannot be generated", format), e.to_string())
+        }
+        (Err(e), _) => Check::Warn(
+            format!("{} cannot be generated, it is optional", format),
+            e.to_string(),
+        ),
+    }
+}
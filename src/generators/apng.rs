@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use tempfile::TempDir;
+
+use super::ffmpeg::{check_encoder, encode_with_ffmpeg, INST_CMD};
+
+/// checks if ffmpeg is available and can encode apng
+pub fn check_for_apng() -> Result<()> {
+    check_encoder(
+        "apng",
+        &format!("please install a more recent version: `{INST_CMD}`"),
+    )
+}
+
+///
+/// generating an animated png in true colors and with alpha, with help of ffmpeg
+pub fn generate_apng_with_ffmpeg(
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    target: &str,
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+) -> Result<()> {
+    encode_with_ffmpeg(
+        time_codes,
+        tempdir,
+        target,
+        start_pause,
+        end_pause,
+        &[
+            ("-pix_fmt", "rgba"),
+            // loops forever, like the gif
+            ("-plays", "0"),
+            ("-f", "apng"),
+        ],
+    )
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use tempfile::TempDir;
+
+use super::ffmpeg::{check_encoder, encode_with_ffmpeg};
+
+/// checks if ffmpeg is available and can encode AV1 with libaom, which is needed for avif
+pub fn check_for_avif() -> Result<()> {
+    check_encoder(
+        "libaom-av1",
+        "please reinstall with the option '--enable-libaom'",
+    )
+}
+
+///
+/// generating an animated avif with AV1 with help of ffmpeg,
+/// much smaller than a gif at the same quality, but slow to encode
+pub fn generate_avif_with_ffmpeg(
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    target: &str,
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+) -> Result<()> {
+    encode_with_ffmpeg(
+        time_codes,
+        tempdir,
+        target,
+        start_pause,
+        end_pause,
+        &[
+            ("-c:v", "libaom-av1"),
+            ("-pix_fmt", "yuv420p"),
+            // constant quality, the bitrate follows what is needed
+            ("-crf", "30"),
+            ("-b:v", "0"),
+            // trades a bit of size for a much faster encoding, the default is painfully slow
+            ("-cpu-used", "6"),
+            ("-row-mt", "1"),
+            ("-f", "avif"),
+        ],
+    )
+}
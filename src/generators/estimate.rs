@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use tempfile::TempDir;
+
+use super::{generate, GifOptions, OutputFormat};
+use crate::Result;
+
+/// number of sample windows, spread over the recording
+const SAMPLE_WINDOWS: usize = 3;
+/// number of consecutive frames per sample window,
+/// consecutive because gif and video encoders compress the difference between frames
+const SAMPLE_WINDOW_FRAMES: usize = 10;
+
+/// the projected size of an output, based on a sample of the frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Estimate {
+    pub bytes: u64,
+    pub sampled_frames: usize,
+    pub total_frames: usize,
+}
+
+///
+/// estimates the size of the output in the given format, by encoding a sample
+/// of the frames with the same settings and extrapolating to all frames.
+/// Nothing is written next to the final output, the sample is encoded into `tempdir`.
+pub fn estimate(
+    format: OutputFormat,
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    gif_options: &GifOptions,
+) -> Result<Estimate> {
+    let sample = sample_time_codes(time_codes);
+    let target = tempdir.path().join("t-rec-estimate");
+    let target = target
+        .to_str()
+        .context("Cannot encode the estimate sample")?;
+    generate(format, &sample, tempdir, target, None, None, gif_options)?;
+    let sample_file = format.file_name(target);
+    let sample_bytes = std::fs::metadata(&sample_file)
+        .with_context(|| format!("Cannot read the size of the sample {}", sample_file))?
+        .len();
+    std::fs::remove_file(&sample_file).ok();
+
+    Ok(Estimate {
+        bytes: extrapolate(sample_bytes, sample.len(), time_codes.len()),
+        sampled_frames: sample.len(),
+        total_frames: time_codes.len(),
+    })
+}
+
+/// picks a few windows of consecutive frames, from the start, the middle and the end
+fn sample_time_codes(time_codes: &[u128]) -> Vec<u128> {
+    let total = time_codes.len();
+    if total <= SAMPLE_WINDOWS * SAMPLE_WINDOW_FRAMES {
+        return time_codes.to_vec();
+    }
+    (0..SAMPLE_WINDOWS)
+        .flat_map(|w| {
+            let start = (total - SAMPLE_WINDOW_FRAMES) * w / (SAMPLE_WINDOWS - 1);
+            time_codes[start..start + SAMPLE_WINDOW_FRAMES]
+                .iter()
+                .copied()
+        })
+        .collect()
+}
+
+fn extrapolate(sample_bytes: u64, sampled_frames: usize, total_frames: usize) -> u64 {
+    if sampled_frames == 0 {
+        return 0;
+    }
+    sample_bytes * total_frames as u64 / sampled_frames as u64
+}
+
+/// file sizes in human readable form, like `1.5 MB`
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// the time it takes to encode all frames, projected from the sample
+pub fn extrapolate_time(sample_time: Duration, estimate: &Estimate) -> Duration {
+    if estimate.sampled_frames == 0 {
+        return Duration::ZERO;
+    }
+    sample_time * estimate.total_frames as u32 / estimate.sampled_frames as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_sample_all_frames_of_short_recordings() {
+        let time_codes = (0..20).collect::<Vec<u128>>();
+        assert_eq!(sample_time_codes(&time_codes), time_codes);
+    }
+
+    #[test]
+    fn should_sample_windows_from_start_middle_and_end() {
+        let time_codes = (0..100).collect::<Vec<u128>>();
+        let sample = sample_time_codes(&time_codes);
+
+        assert_eq!(sample.len(), SAMPLE_WINDOWS * SAMPLE_WINDOW_FRAMES);
+        assert_eq!(sample[..10], (0..10).collect::<Vec<u128>>()[..]);
+        assert_eq!(sample[10..20], (45..55).collect::<Vec<u128>>()[..]);
+        assert_eq!(sample[20..], (90..100).collect::<Vec<u128>>()[..]);
+    }
+
+    #[test]
+    fn should_extrapolate_to_all_frames() {
+        assert_eq!(extrapolate(3_000, 30, 300), 30_000);
+        assert_eq!(extrapolate(0, 0, 0), 0);
+    }
+
+    #[test]
+    fn should_format_bytes_human_readable() {
+        assert_eq!(human_bytes(999), "999 B");
+        assert_eq!(human_bytes(1_500), "1.5 KB");
+        assert_eq!(human_bytes(2_340_000), "2.3 MB");
+    }
+
+    #[cfg(feature = "e2e_tests")]
+    #[test]
+    fn should_estimate_mp4_from_a_sample() -> Result<()> {
+        use crate::utils::{file_name_for, IMG_EXT};
+        use image::{Rgba, RgbaImage};
+
+        // given a recording of 300 frames, with something moving
+        let tempdir = TempDir::new()?;
+        let time_codes = (0..300).map(|i| i * 250).collect::<Vec<u128>>();
+        for (i, tc) in time_codes.iter().enumerate() {
+            let frame = RgbaImage::from_fn(64, 48, |x, y| {
+                let on = (x as usize + i) % 64 < 8 || (y as usize + i) % 48 < 4;
+                Rgba(if on {
+                    [0xff, 0xff, 0, 0xff]
+                } else {
+                    [0, 0, 0, 0xff]
+                })
+            });
+            frame.save(tempdir.path().join(file_name_for(tc, IMG_EXT)))?;
+        }
+
+        // when
+        let estimation = estimate(
+            OutputFormat::Mp4,
+            &time_codes,
+            &tempdir,
+            &GifOptions::default(),
+        )?;
+
+        // then only the sample was encoded, and it projects the real size roughly
+        assert_eq!(
+            estimation.sampled_frames,
+            SAMPLE_WINDOWS * SAMPLE_WINDOW_FRAMES
+        );
+        assert_eq!(estimation.total_frames, 300);
+        let target = tempdir.path().join("t-rec-full");
+        let target = target.to_str().unwrap();
+        generate(
+            OutputFormat::Mp4,
+            &time_codes,
+            &tempdir,
+            target,
+            None,
+            None,
+            &GifOptions::default(),
+        )?;
+        let bytes = std::fs::metadata(OutputFormat::Mp4.file_name(target))?.len();
+        assert!(
+            estimation.bytes < bytes * 3 && estimation.bytes > bytes / 3,
+            "estimated {} bytes for {} bytes",
+            estimation.bytes,
+            bytes
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use tempfile::TempDir;
+
+use crate::utils::{file_name_for, IMG_EXT};
+use crate::Result;
+
+const CONCAT_FILE: &str = "t-rec-frames.ffconcat";
+
+///
+/// writes a list of all frames with their individual duration for ffmpeg's concat demuxer,
+/// so that formats with variable frame timing get the same timing as the gif.
+/// ffmpeg reads the frames one by one from that list, so they are never all in memory.
+///
+/// ```text
+/// ffconcat version 1.0
+/// file '/tmp/.tmpX/t-rec-frame-000000251.bmp'
+/// duration 0.251
+/// ```
+pub fn write_concat_list(
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+) -> Result<PathBuf> {
+    let list = tempdir.path().join(CONCAT_FILE);
+    let file = File::create(&list).context("Cannot create the list of frames for ffmpeg")?;
+    let mut out = BufWriter::new(file);
+    writeln!(out, "ffconcat version 1.0")?;
+    let mut last_frame = None;
+    for (i, duration) in frame_durations(time_codes, start_pause, end_pause)
+        .into_iter()
+        .enumerate()
+    {
+        let frame = tempdir.path().join(file_name_for(&time_codes[i], IMG_EXT));
+        if !frame.exists() {
+            continue;
+        }
+        writeln!(out, "file '{}'", frame.display())?;
+        writeln!(out, "duration {:.3}", duration.as_secs_f64())?;
+        last_frame = Some(frame);
+    }
+    // the duration of the last entry is ignored by ffmpeg, unless the file is listed once more
+    if let Some(frame) = last_frame {
+        writeln!(out, "file '{}'", frame.display())?;
+    }
+    out.flush()
+        .context("Cannot write the list of frames for ffmpeg")?;
+
+    Ok(list)
+}
+
+/// every frame is shown for the time since the previous one, plus the pauses at start and end, like in the gif
+pub(super) fn frame_durations(
+    time_codes: &[u128],
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+) -> Vec<Duration> {
+    let mut previous = 0;
+    let last = time_codes.len().saturating_sub(1);
+    time_codes
+        .iter()
+        .enumerate()
+        .map(|(i, tc)| {
+            let mut duration = Duration::from_millis((*tc - previous) as u64);
+            previous = *tc;
+            if i == 0 {
+                duration += start_pause.unwrap_or_default();
+            }
+            if i == last {
+                duration += end_pause.unwrap_or_default();
+            }
+            duration
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_calculate_frame_durations() {
+        let durations = frame_durations(
+            &[250, 500, 1250],
+            Some(Duration::from_secs(1)),
+            Some(Duration::from_secs(2)),
+        );
+
+        assert_eq!(
+            durations,
+            vec![
+                Duration::from_millis(1250),
+                Duration::from_millis(250),
+                Duration::from_millis(2750)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_the_pause_of_deduplicated_frames() -> Result<()> {
+        // given the frames in between were identical and got dropped, e.g. with `--dedup`
+        let tempdir = TempDir::new()?;
+        for tc in [250, 1000] {
+            std::fs::write(tempdir.path().join(file_name_for(&tc, IMG_EXT)), "")?;
+        }
+
+        // when
+        let list = write_concat_list(&[250, 1000], &tempdir, None, None)?;
+
+        // then the pause is kept, as in the gif
+        let list = std::fs::read_to_string(list)?;
+        let frame = |tc| tempdir.path().join(file_name_for(&tc, IMG_EXT));
+        assert_eq!(
+            list,
+            format!(
+                "ffconcat version 1.0\n\
+                 file '{0}'\nduration 0.250\n\
+                 file '{1}'\nduration 0.750\n\
+                 file '{1}'\n",
+                frame(250).display(),
+                frame(1000).display()
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tempfile::TempDir;
+
+use super::ffconcat::write_concat_list;
+
+pub const PROGRAM: &str = "ffmpeg";
+
+#[cfg(target_os = "macos")]
+pub const INST_CMD: &str = "brew install ffmpeg";
+#[cfg(not(target_os = "macos"))]
+pub const INST_CMD: &str = "apt-get install ffmpeg";
+
+///
+/// checks if ffmpeg is available and knows the given encoder,
+/// the `hint` tells how to get an ffmpeg that has it
+pub fn check_encoder(encoder: &str, hint: &str) -> Result<()> {
+    let out = Command::new(PROGRAM)
+        .args(["-hide_banner", "-encoders"])
+        .output()
+        .with_context(|| {
+            format!("There is an issue with '{PROGRAM}', please install: `{INST_CMD}`")
+        })?;
+
+    if !String::from_utf8_lossy(&out.stdout).contains(&format!(" {encoder} ")) {
+        anyhow::bail!("ffmpeg does not support the '{encoder}' encoder, {hint}")
+    }
+
+    Ok(())
+}
+
+///
+/// encodes the frames with ffmpeg, every frame keeps its own duration like in the gif.
+/// The frames are streamed into ffmpeg from a concat list, the `codec_args` are option and value pairs
+/// that pick the format, like `("-c:v", "libvpx-vp9")`
+pub fn encode_with_ffmpeg(
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    target: &str,
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+    codec_args: &[(&str, &str)],
+) -> Result<()> {
+    println!("🎉 🎬 Generating {target}");
+    let frames = write_concat_list(time_codes, tempdir, start_pause, end_pause)?;
+    let mut cmd = Command::new(PROGRAM);
+    cmd.arg("-y")
+        .args(["-f", "concat", "-safe", "0"])
+        .arg("-i")
+        .arg(frames)
+        // keeps the individual frame durations, instead of a constant frame rate
+        .args([fps_mode_option(), "vfr"]);
+    for (option, value) in codec_args {
+        cmd.arg(option).arg(value);
+    }
+    let e = cmd
+        .arg(target)
+        .output()
+        .with_context(|| format!("Cannot start '{PROGRAM}' to generate {target}"))?;
+
+    if !e.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&e.stderr))
+    }
+
+    Ok(())
+}
+
+///
+/// `-fps_mode` replaced the deprecated `-vsync` in ffmpeg 5.1, older versions only know `-vsync`
+fn fps_mode_option() -> &'static str {
+    let version = Command::new(PROGRAM)
+        .arg("-version")
+        .output()
+        .ok()
+        .and_then(|out| super::version_line(&out.stdout));
+    match version.as_deref().and_then(release) {
+        Some(release) if release < (5, 1) => "-vsync",
+        _ => "-fps_mode",
+    }
+}
+
+///
+/// the major and minor version of an ffmpeg release, from the first line of `ffmpeg -version`.
+/// Builds from git have no release version, they are newer than any release
+fn release(version_line: &str) -> Option<(u32, u32)> {
+    let version = version_line.strip_prefix("ffmpeg version ")?;
+    let version = version.strip_prefix('n').unwrap_or(version);
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts
+        .next()
+        .and_then(|minor| minor.parse().ok())
+        .unwrap_or(0);
+    Some((major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_the_release_of_ffmpeg() {
+        assert_eq!(
+            release("ffmpeg version 4.4.2-0ubuntu0.22.04.1"),
+            Some((4, 4))
+        );
+        assert_eq!(release("ffmpeg version 5.1"), Some((5, 1)));
+        assert_eq!(release("ffmpeg version n6.0"), Some((6, 0)));
+        assert_eq!(release("ffmpeg version 7.1.1"), Some((7, 1)));
+        assert_eq!(release("ffmpeg version N-109000-g8fe3a2b"), None);
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+
+use super::ffconcat::frame_durations;
+use super::gif::frame_delays;
+use crate::utils::{file_name_for, IMG_EXT};
+use crate::Result;
+
+pub const MANIFEST_FILE: &str = "frames.json";
+
+///
+/// exports the processed frames into `dir`, named by their time code like the recorded frames,
+/// so that `t-rec encode` can read them again. Plus a manifest with the timing of every frame,
+/// exactly as the output has it, e.g. for own ffmpeg filters
+///
+/// ```json
+/// {
+///   "frames": [
+///     { "file": "t-rec-frame-000000250.bmp", "timestamp_ms": 0, "duration_ms": 1250 },
+///     { "file": "t-rec-frame-000000500.bmp", "timestamp_ms": 1250, "duration_ms": 250 }
+///   ]
+/// }
+/// ```
+pub fn export_frames(
+    time_codes: &[u128],
+    tempdir: &Path,
+    dir: impl AsRef<Path>,
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+    gif: bool,
+) -> Result<()> {
+    let dir = dir.as_ref();
+    println!("🖼  Exporting frames to {}", dir.display());
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Cannot create frames directory {}", dir.display()))?;
+
+    let file = File::create(dir.join(MANIFEST_FILE))
+        .with_context(|| format!("Cannot create {} in {}", MANIFEST_FILE, dir.display()))?;
+    let mut out = BufWriter::new(file);
+    writeln!(out, "{{\n  \"frames\": [")?;
+    let timings = frame_timings(time_codes, start_pause, end_pause, gif);
+    for (i, (tc, (timestamp, duration))) in time_codes.iter().zip(timings).enumerate() {
+        let name = file_name_for(tc, IMG_EXT);
+        fs::copy(tempdir.join(file_name_for(tc, IMG_EXT)), dir.join(&name))
+            .with_context(|| format!("Cannot export frame {}", name))?;
+        let separator = if i + 1 < time_codes.len() { "," } else { "" };
+        writeln!(
+            out,
+            "    {{ \"file\": \"{}\", \"timestamp_ms\": {}, \"duration_ms\": {} }}{}",
+            name, timestamp, duration, separator
+        )?;
+    }
+    writeln!(out, "  ]\n}}")?;
+    out.flush().context("Cannot write the frames manifest")
+}
+
+///
+/// the timestamp and the duration of every frame in the output, in milliseconds.
+/// A gif knows only centiseconds, so with a gif the rounded delays are taken to match it exactly
+pub(super) fn frame_timings(
+    time_codes: &[u128],
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+    gif: bool,
+) -> Vec<(u128, u128)> {
+    let durations: Vec<u128> = if gif {
+        frame_delays(time_codes, start_pause, end_pause)
+            .into_iter()
+            .map(|delay| delay as u128 * 10)
+            .collect()
+    } else {
+        frame_durations(time_codes, start_pause, end_pause)
+            .into_iter()
+            .map(|duration| duration.as_millis())
+            .collect()
+    };
+    let mut timestamp = 0;
+    durations
+        .into_iter()
+        .map(|duration| {
+            let timing = (timestamp, duration);
+            timestamp += duration;
+            timing
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn should_match_the_gif_timing() {
+        let time_codes = [259, 508, 1250];
+
+        assert_eq!(
+            frame_timings(&time_codes, None, None, true),
+            vec![(0, 250), (250, 240), (490, 740)]
+        );
+        assert_eq!(
+            frame_timings(&time_codes, None, None, false),
+            vec![(0, 259), (259, 249), (508, 742)]
+        );
+    }
+
+    #[test]
+    fn should_export_frames_with_manifest() -> Result<()> {
+        let frames = TempDir::new()?;
+        for tc in [250, 500] {
+            fs::write(
+                frames.path().join(file_name_for(&tc, IMG_EXT)),
+                tc.to_string(),
+            )?;
+        }
+        let target = TempDir::new()?;
+        let dir = target.path().join("frames");
+
+        export_frames(
+            &[250, 500],
+            frames.path(),
+            &dir,
+            Some(Duration::from_secs(1)),
+            None,
+            false,
+        )?;
+
+        assert_eq!(
+            fs::read_to_string(dir.join(file_name_for(&500, IMG_EXT)))?,
+            "500"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join(MANIFEST_FILE))?,
+            "{\n  \"frames\": [\n    \
+             { \"file\": \"t-rec-frame-000000250.bmp\", \"timestamp_ms\": 0, \"duration_ms\": 1250 },\n    \
+             { \"file\": \"t-rec-frame-000000500.bmp\", \"timestamp_ms\": 1250, \"duration_ms\": 250 }\n  \
+             ]\n}\n"
+        );
+
+        Ok(())
+    }
+}
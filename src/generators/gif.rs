@@ -7,6 +7,8 @@ use std::time::Duration;
 use tempfile::TempDir;
 
 const PROGRAM: &str = "convert";
+/// the in-memory image that holds the shared palette of all frames
+const PALETTE: &str = "mpr:t-rec-palette";
 #[cfg(target_os = "macos")]
 const INST_CMD: &str = "brew install imagemagick";
 #[cfg(not(target_os = "macos"))]
@@ -24,40 +26,61 @@ pub fn check_for_imagemagick() -> Result<Output> {
         })
 }
 
+/// the version of imagemagick, if installed
+pub fn imagemagick_version() -> Option<String> {
+    check_for_imagemagick()
+        .ok()
+        .and_then(|out| super::version_line(&out.stdout))
+}
+
+/// options that only apply to gifs
+#[derive(Debug, Default, Clone)]
+pub struct GifOptions {
+    /// keeps the transparent areas of the frames transparent, instead of flattening them
+    pub transparent: bool,
+    /// the number of colors in the palette, `None` for imagemagick's default of 256
+    pub colors: Option<u32>,
+    /// turns dithering on or off, `None` for imagemagick's default
+    pub dither: Option<bool>,
+    /// one palette for all frames instead of one per frame, avoids colors flickering between frames
+    pub global_palette: bool,
+}
+
 ///
 /// generating the final gif with help of convert
+///
+/// For a transparent gif every frame gets disposed before the next one is drawn,
+/// otherwise the transparent areas would show the previous frames.
+/// Since a gif knows only fully transparent or opaque pixels, the alpha channel is cut at 50%.
 pub fn generate_gif_with_convert(
     time_codes: &[u128],
     tempdir: &TempDir,
     target: &str,
     start_pause: Option<Duration>,
     end_pause: Option<Duration>,
+    options: &GifOptions,
 ) -> Result<()> {
     println!("🎉 🚀 Generating {target}");
     let mut cmd = Command::new(PROGRAM);
     cmd.arg("-loop").arg("0");
-    let mut delay = 0;
+    if options.transparent {
+        cmd.args(["-dispose", "Background"]);
+    }
     let temp = tempdir.path();
-    let last_frame_i = time_codes.len() - 1;
-    for (i, tc) in time_codes.iter().enumerate() {
-        delay = *tc - delay;
+    for (tc, frame_delay) in time_codes
+        .iter()
+        .zip(frame_delays(time_codes, start_pause, end_pause))
+    {
         let frame = temp.join(file_name_for(tc, IMG_EXT));
         if !frame.exists() {
             continue;
         }
-        let mut frame_delay = (delay as f64 * 0.1) as u64;
-        match (i, start_pause, end_pause) {
-            (0, Some(delay), _) => {
-                frame_delay += delay.as_millis().div(10) as u64;
-            }
-            (i, _, Some(delay)) if i == last_frame_i => {
-                frame_delay += delay.as_millis().div(10) as u64;
-            }
-            (_, _, _) => {}
-        }
         cmd.arg("-delay").arg(frame_delay.to_string()).arg(frame);
-        delay = *tc;
     }
+    if options.transparent {
+        cmd.args(["-channel", "A", "-threshold", "50%", "+channel"]);
+    }
+    cmd.args(palette_args(options));
     cmd.arg("-layers")
         .arg("Optimize")
         .arg(target)
@@ -66,3 +89,152 @@ pub fn generate_gif_with_convert(
 
     Ok(())
 }
+
+///
+/// the delay of every frame in centiseconds, the unit of a gif.
+/// Every frame is shown for the time since the previous one, the pauses go to the first and the last frame.
+pub fn frame_delays(
+    time_codes: &[u128],
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+) -> Vec<u64> {
+    let mut previous = 0;
+    let last_frame_i = time_codes.len().saturating_sub(1);
+    time_codes
+        .iter()
+        .enumerate()
+        .map(|(i, tc)| {
+            let mut frame_delay = ((*tc - previous) as f64 * 0.1) as u64;
+            previous = *tc;
+            match (i, start_pause, end_pause) {
+                (0, Some(delay), _) => {
+                    frame_delay += delay.as_millis().div(10) as u64;
+                }
+                (i, _, Some(delay)) if i == last_frame_i => {
+                    frame_delay += delay.as_millis().div(10) as u64;
+                }
+                (_, _, _) => {}
+            }
+            frame_delay
+        })
+        .collect()
+}
+
+///
+/// the color reduction of all frames, nothing for the defaults, so that the output stays as it was.
+///
+/// `+remap` builds the shared palette with up to 256 colors, no matter how many colors the frames
+/// were reduced to before. So with a limit the shared palette is made from all frames explicitly:
+///
+/// ```sh
+/// convert ... \
+///     \( -clone 0--1 -append -colors 128 -unique-colors -write mpr:t-rec-palette +delete \) \
+///     -remap mpr:t-rec-palette \
+///     t-rec.gif
+/// ```
+fn palette_args(options: &GifOptions) -> Vec<String> {
+    let mut args = vec![];
+    match options.dither {
+        Some(true) => args.extend(["-dither".to_string(), "FloydSteinberg".to_string()]),
+        Some(false) => args.push("+dither".to_string()),
+        None => {}
+    }
+    match (options.colors, options.global_palette) {
+        (Some(colors), true) => {
+            let colors = colors.to_string();
+            args.extend(
+                [
+                    "(",
+                    "-clone",
+                    "0--1",
+                    "-append",
+                    "-colors",
+                    &colors,
+                    "-unique-colors",
+                    "-write",
+                    PALETTE,
+                    "+delete",
+                    ")",
+                    "-remap",
+                    PALETTE,
+                ]
+                .map(String::from),
+            );
+        }
+        (Some(colors), false) => args.extend(["-colors".to_string(), colors.to_string()]),
+        (None, true) => args.push("+remap".to_string()),
+        (None, false) => {}
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_calculate_frame_delays_in_centiseconds() {
+        let delays = frame_delays(
+            &[259, 508, 1250],
+            Some(Duration::from_secs(1)),
+            Some(Duration::from_millis(2009)),
+        );
+
+        assert_eq!(delays, vec![125, 24, 274]);
+    }
+
+    #[test]
+    fn should_keep_defaults_without_palette_options() {
+        assert!(palette_args(&GifOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn should_reduce_colors_with_global_palette() {
+        let options = GifOptions {
+            colors: Some(128),
+            dither: Some(false),
+            global_palette: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            palette_args(&options),
+            vec![
+                "+dither",
+                "(",
+                "-clone",
+                "0--1",
+                "-append",
+                "-colors",
+                "128",
+                "-unique-colors",
+                "-write",
+                "mpr:t-rec-palette",
+                "+delete",
+                ")",
+                "-remap",
+                "mpr:t-rec-palette"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reduce_colors_per_frame_without_global_palette() {
+        let options = GifOptions {
+            colors: Some(64),
+            ..Default::default()
+        };
+
+        assert_eq!(palette_args(&options), vec!["-colors", "64"]);
+    }
+
+    #[test]
+    fn should_remap_to_a_full_global_palette_without_a_color_limit() {
+        let options = GifOptions {
+            global_palette: true,
+            ..Default::default()
+        };
+
+        assert_eq!(palette_args(&options), vec!["+remap"]);
+    }
+}
@@ -1,7 +1,205 @@
+mod apng;
+mod avif;
+mod estimate;
+mod ffconcat;
+mod ffmpeg;
+mod frames;
 mod gif;
 mod mp4;
+mod timings;
+mod webm;
 
-pub use self::gif::check_for_imagemagick as check_for_gif;
-pub use self::gif::generate_gif_with_convert as generate_gif;
-pub use self::mp4::check_for_ffmpeg as check_for_mp4;
-pub use self::mp4::generate_mp4_with_ffmpeg as generate_mp4;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use tempfile::TempDir;
+
+use crate::utils::{APNG_EXT, AVIF_EXT, DEFAULT_EXT, MOVIE_EXT, WEBM_EXT};
+use crate::Result;
+
+use self::apng::check_for_apng;
+use self::apng::generate_apng_with_ffmpeg as generate_apng;
+use self::avif::check_for_avif;
+use self::avif::generate_avif_with_ffmpeg as generate_avif;
+pub use self::estimate::{estimate, extrapolate_time, human_bytes};
+pub use self::frames::export_frames;
+use self::gif::check_for_imagemagick as check_for_gif;
+use self::gif::generate_gif_with_convert as generate_gif;
+use self::gif::imagemagick_version as gif_tool_version;
+pub use self::gif::GifOptions;
+use self::mp4::check_for_ffmpeg as check_for_mp4;
+use self::mp4::ffmpeg_version as mp4_tool_version;
+use self::mp4::generate_mp4_with_ffmpeg as generate_mp4;
+pub use self::timings::{export_timings, TIMINGS_EXT};
+use self::webm::check_for_webm;
+use self::webm::generate_webm_with_ffmpeg as generate_webm;
+
+/// all the formats t-rec can generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Gif,
+    Mp4,
+    Apng,
+    Webm,
+    Avif,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 5] = [
+        OutputFormat::Gif,
+        OutputFormat::Mp4,
+        OutputFormat::Apng,
+        OutputFormat::Webm,
+        OutputFormat::Avif,
+    ];
+
+    /// the file extension of this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Gif => DEFAULT_EXT,
+            OutputFormat::Mp4 => MOVIE_EXT,
+            OutputFormat::Apng => APNG_EXT,
+            OutputFormat::Webm => WEBM_EXT,
+            OutputFormat::Avif => AVIF_EXT,
+        }
+    }
+
+    /// the name of this format, as users know it
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Apng => "apng",
+            _ => self.extension(),
+        }
+    }
+
+    /// the file name for a target without extension
+    pub fn file_name(&self, target: &str) -> String {
+        format!("{}.{}", target, self.extension())
+    }
+
+    /// checks if the tools needed to generate this format are installed
+    pub fn check(&self) -> Result<()> {
+        match self {
+            OutputFormat::Gif => check_for_gif().map(|_| ()),
+            OutputFormat::Mp4 => check_for_mp4(),
+            OutputFormat::Apng => check_for_apng(),
+            OutputFormat::Webm => check_for_webm(),
+            OutputFormat::Avif => check_for_avif(),
+        }
+    }
+
+    /// the version of the tool that generates this format, `None` if it is not installed
+    pub fn tool_version(&self) -> Option<String> {
+        match self {
+            OutputFormat::Gif => gif_tool_version(),
+            OutputFormat::Mp4 | OutputFormat::Apng | OutputFormat::Webm | OutputFormat::Avif => {
+                mp4_tool_version()
+            }
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// returns the output formats that can be generated with the tools installed
+pub fn available_output_formats() -> Vec<OutputFormat> {
+    OutputFormat::ALL
+        .iter()
+        .copied()
+        .filter(|format| format.check().is_ok())
+        .collect()
+}
+
+/// the generators expect strictly increasing time codes, this sorts them and drops duplicates,
+/// since a duplicate refers to the very same frame file.
+/// Returns `true` if anything had to be fixed.
+pub fn ensure_monotonic(time_codes: &mut Vec<u128>) -> bool {
+    if time_codes.windows(2).all(|w| w[0] < w[1]) {
+        return false;
+    }
+    time_codes.sort_unstable();
+    time_codes.dedup();
+    true
+}
+
+/// extracts the version from the output of e.g. `convert --version` or `ffmpeg -version`,
+/// that is the first line without the noise around it
+fn version_line(output: &[u8]) -> Option<String> {
+    let output = String::from_utf8_lossy(output);
+    let line = output.lines().next()?;
+    let line = line.strip_prefix("Version: ").unwrap_or(line);
+    let line = line.split(" Copyright").next().unwrap_or(line).trim();
+    (!line.is_empty()).then(|| line.to_string())
+}
+
+///
+/// generates the final output in the given format,
+/// the `target` is the file name without extension
+pub fn generate(
+    format: OutputFormat,
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    target: &str,
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+    gif_options: &GifOptions,
+) -> Result<()> {
+    let target = format.file_name(target);
+    match format {
+        OutputFormat::Gif => generate_gif(
+            time_codes,
+            tempdir,
+            &target,
+            start_pause,
+            end_pause,
+            gif_options,
+        ),
+        OutputFormat::Mp4 => generate_mp4(time_codes, tempdir, &target, start_pause, end_pause),
+        OutputFormat::Apng => generate_apng(time_codes, tempdir, &target, start_pause, end_pause),
+        OutputFormat::Webm => generate_webm(time_codes, tempdir, &target, start_pause, end_pause),
+        OutputFormat::Avif => generate_avif(time_codes, tempdir, &target, start_pause, end_pause),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_formats_as_extension() {
+        assert_eq!(OutputFormat::Gif.to_string(), "gif");
+        assert_eq!(OutputFormat::Mp4.to_string(), "mp4");
+    }
+
+    #[test]
+    fn should_name_apng_with_png_extension() {
+        assert_eq!(OutputFormat::Apng.to_string(), "apng");
+        assert_eq!(OutputFormat::Apng.file_name("t-rec"), "t-rec.png");
+    }
+
+    #[test]
+    fn should_sort_out_of_order_time_codes() {
+        let mut time_codes = vec![0, 250, 750, 500, 750, 1000];
+
+        assert!(ensure_monotonic(&mut time_codes));
+        assert_eq!(time_codes, vec![0, 250, 500, 750, 1000]);
+        assert!(!ensure_monotonic(&mut time_codes));
+    }
+
+    #[test]
+    fn should_extract_tool_version() {
+        assert_eq!(
+            version_line(b"Version: ImageMagick 6.9.11-60 Q16 x86_64 2021-01-25 https://imagemagick.org\nCopyright: (C) 1999-2021 ImageMagick Studio LLC\n"),
+            Some("ImageMagick 6.9.11-60 Q16 x86_64 2021-01-25 https://imagemagick.org".to_string())
+        );
+        assert_eq!(
+            version_line(b"ffmpeg version 4.4.2 Copyright (c) 2000-2021 the FFmpeg developers\nbuilt with gcc 11\n"),
+            Some("ffmpeg version 4.4.2".to_string())
+        );
+        assert_eq!(version_line(b""), None);
+    }
+}
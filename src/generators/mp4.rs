@@ -1,16 +1,10 @@
 use std::process::Command;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use tempfile::TempDir;
 
-use crate::utils::IMG_EXT;
-
-const PROGRAM: &str = "ffmpeg";
-
-#[cfg(target_os = "macos")]
-const INST_CMD: &str = "brew install ffmpeg";
-#[cfg(not(target_os = "macos"))]
-const INST_CMD: &str = "apt-get install ffmpeg";
+use super::ffmpeg::{encode_with_ffmpeg, INST_CMD, PROGRAM};
 
 /// checks if ffmpeg is available
 pub fn check_for_ffmpeg() -> Result<()> {
@@ -32,37 +26,38 @@ pub fn check_for_ffmpeg() -> Result<()> {
     Ok(())
 }
 
-/// a nice resource that illustrates the power of ffmpeg
-/// https://hamelot.io/visualization/using-ffmpeg-to-convert-a-set-of-images-into-a-video/
+/// the version of ffmpeg, if installed
+pub fn ffmpeg_version() -> Option<String> {
+    let out = Command::new(PROGRAM).arg("-version").output().ok()?;
+    let version = super::version_line(&out.stdout)?;
+    if String::from_utf8_lossy(&out.stdout).contains("--enable-libx264") {
+        Some(version)
+    } else {
+        Some(format!("{version} (without libx264)"))
+    }
+}
+
 ///
-/// generating the final mp4 with help of ffmpeg
+/// generating the final mp4 with help of ffmpeg,
+/// every frame keeps its own duration, e.g. with `--on-change` or `--dedup`
 pub fn generate_mp4_with_ffmpeg(
-    _time_codes: &[u128],
+    time_codes: &[u128],
     tempdir: &TempDir,
     target: &str,
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
 ) -> Result<()> {
-    println!("🎉 🎬 Generating {target}");
-    Command::new(PROGRAM)
-        .arg("-y")
-        .arg("-r")
-        // framerate
-        .arg("4")
-        .arg("-f")
-        .arg("image2")
-        .arg("-pattern_type")
-        .arg("glob")
-        .arg("-i")
-        .arg(tempdir.path().join(format!("*.{IMG_EXT}")))
-        .arg("-vcodec")
-        .arg("libx264")
-        .arg("-pix_fmt")
-        .arg("yuv420p")
-        // fixes: [libx264 @ 0x7fc216019000] height not divisible by 2 (650x477)
-        .arg("-vf")
-        .arg("pad='width=ceil(iw/2)*2:height=ceil(ih/2)*2'")
-        // end of fix
-        .arg(target)
-        .output()
-        .with_context(|| format!("Cannot start '{PROGRAM}' to generate the final video"))
-        .map(|_| ())
+    encode_with_ffmpeg(
+        time_codes,
+        tempdir,
+        target,
+        start_pause,
+        end_pause,
+        &[
+            ("-vcodec", "libx264"),
+            ("-pix_fmt", "yuv420p"),
+            // fixes: [libx264 @ 0x7fc216019000] height not divisible by 2 (650x477)
+            ("-vf", "pad='width=ceil(iw/2)*2:height=ceil(ih/2)*2'"),
+        ],
+    )
 }
@@ -0,0 +1,73 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+
+use super::frames::frame_timings;
+use crate::Result;
+
+pub const TIMINGS_EXT: &str = "timings.csv";
+
+///
+/// exports the timestamp of every frame as csv sidecar file, relative to the start of the output,
+/// pauses and cards included, so that other tools can reconstruct the exact timing
+///
+/// ```csv
+/// frame,timestamp_ms,duration_ms
+/// 0,0,250
+/// 1,250,240
+/// ```
+pub fn export_timings(
+    time_codes: &[u128],
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+    gif: bool,
+    target: impl AsRef<Path>,
+) -> Result<()> {
+    let target = target.as_ref();
+    println!("🕒 Exporting timings to {}", target.display());
+    let file = File::create(target)
+        .with_context(|| format!("Cannot create timings file {}", target.display()))?;
+    let mut out = BufWriter::new(file);
+    writeln!(out, "frame,timestamp_ms,duration_ms")?;
+    for (i, (timestamp, duration)) in frame_timings(time_codes, start_pause, end_pause, gif)
+        .into_iter()
+        .enumerate()
+    {
+        writeln!(out, "{},{},{}", i, timestamp, duration)?;
+    }
+    out.flush().context("Cannot write timings file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn should_export_timings() -> Result<()> {
+        let dir = TempDir::new()?;
+        let target = dir.path().join(format!("t-rec.{}", TIMINGS_EXT));
+
+        export_timings(
+            &[251, 502, 1000],
+            Some(Duration::from_secs(1)),
+            None,
+            true,
+            &target,
+        )?;
+
+        let content = std::fs::read_to_string(target)?;
+        assert_eq!(
+            content,
+            "frame,timestamp_ms,duration_ms\n\
+             0,0,1250\n\
+             1,1250,250\n\
+             2,1500,490\n"
+        );
+
+        Ok(())
+    }
+}
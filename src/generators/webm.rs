@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use tempfile::TempDir;
+
+use super::ffmpeg::{check_encoder, encode_with_ffmpeg};
+
+/// checks if ffmpeg is available and can encode VP9, which is needed for the alpha channel in webm
+pub fn check_for_webm() -> Result<()> {
+    check_encoder(
+        "libvpx-vp9",
+        "please reinstall with the option '--enable-libvpx'",
+    )
+}
+
+///
+/// generating a webm video with VP9 and alpha channel with help of ffmpeg,
+/// so that transparent backgrounds stay transparent on the web
+pub fn generate_webm_with_ffmpeg(
+    time_codes: &[u128],
+    tempdir: &TempDir,
+    target: &str,
+    start_pause: Option<Duration>,
+    end_pause: Option<Duration>,
+) -> Result<()> {
+    encode_with_ffmpeg(
+        time_codes,
+        tempdir,
+        target,
+        start_pause,
+        end_pause,
+        &[
+            ("-c:v", "libvpx-vp9"),
+            ("-pix_fmt", "yuva420p"),
+            // alt-ref frames drop the alpha channel with some libvpx versions
+            ("-auto-alt-ref", "0"),
+            // constant quality, the bitrate follows what is needed
+            ("-crf", "30"),
+            ("-b:v", "0"),
+        ],
+    )
+}
@@ -1,10 +1,44 @@
 mod x11_api;
 
+use crate::common::ColorProfiles;
 use crate::{PlatformApi, Result};
+use anyhow::bail;
 use x11_api::X11Api;
 
 pub fn setup() -> Result<impl PlatformApi> {
+    // on wayland only XWayland windows can be captured, via X11
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() && std::env::var_os("DISPLAY").is_none() {
+        bail!(
+            "This is a Wayland session without XWayland, which t-rec cannot capture yet.\n\
+             Please enable XWayland (so that DISPLAY is set) and run your terminal with it, \
+             e.g. with `GDK_BACKEND=x11` or `WINIT_UNIX_BACKEND=x11`."
+        );
+    }
     X11Api::new()
 }
 
+/// captured frames are already in sRGB, there is nothing to normalize
+pub fn color_profiles(_window_id: u64) -> Result<Option<ColorProfiles>> {
+    Ok(None)
+}
+
 pub const DEFAULT_SHELL: &str = "/bin/sh";
+
+/// sets the nice level of the current thread, this is best effort.
+/// NOTE: negative values require root or the `CAP_SYS_NICE` capability
+pub fn set_thread_priority(nice: i32) -> Result<()> {
+    // on linux the nice level is per thread, elsewhere per process
+    #[cfg(target_os = "linux")]
+    let who = unsafe { libc::gettid() } as libc::id_t;
+    #[cfg(not(target_os = "linux"))]
+    let who = 0;
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, who, nice) } != 0 {
+        bail!(
+            "Cannot set the capture thread priority to {}: {}",
+            nice,
+            std::io::Error::last_os_error()
+        );
+    }
+
+    Ok(())
+}
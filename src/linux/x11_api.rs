@@ -1,10 +1,9 @@
 use crate::common::identify_transparency::identify_transparency;
-use crate::common::image::convert_bgra_to_rgba;
-use crate::{ImageOnHeap, Margin, PlatformApi, Result, WindowId, WindowList};
+use crate::common::image::{from_raw_pixels, AlphaMode, ChannelOrder};
+use crate::common::{Calibration, CalibrationCache, Rect};
+use crate::{ImageOnHeap, PlatformApi, Result, WindowId, WindowList};
 
 use anyhow::Context;
-use image::flat::SampleLayout;
-use image::{ColorType, FlatSamples};
 use log::debug;
 use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
@@ -31,7 +30,7 @@ pub struct X11Api {
     conn: RustConnection<DefaultStream>,
     screen_num: usize,
     atoms: Atoms,
-    margin: Option<Margin>,
+    calibrations: CalibrationCache,
 }
 
 impl X11Api {
@@ -42,7 +41,7 @@ impl X11Api {
             conn,
             screen_num,
             atoms,
-            margin: None,
+            calibrations: CalibrationCache::default(),
         })
     }
 
@@ -123,40 +122,17 @@ impl X11Api {
         let geom = conn.get_geometry(window)?.reply()?;
         Ok((geom.x, geom.y, geom.width, geom.height))
     }
-}
-
-impl PlatformApi for X11Api {
-    /// 1. error if no screenshot is capture-able
-    /// 2. it checks for transparent margins and configures the api
-    ///     to cut them away in further screenshots
-    fn calibrate(&mut self, window_id: WindowId) -> Result<()> {
-        let image = self.capture_window_screenshot(window_id)?;
-        self.margin = identify_transparency(*image)?;
-
-        Ok(())
-    }
-
-    fn window_list(&self) -> Result<WindowList> {
-        let windows = self.get_visible_windows()?;
-        let mut wins = vec![];
-        for window in windows {
-            if let Ok(Some(name)) = self.get_window_name(&window) {
-                let name = if let Ok((_, _, w, h)) = self.get_window_geometry(&window) {
-                    format!("{} ({}x{})", name, w, h)
-                } else {
-                    name
-                };
-                wins.push((Some(name), window));
-            }
-        }
 
-        Ok(wins)
-    }
-
-    fn capture_window_screenshot(&self, window_id: WindowId) -> Result<ImageOnHeap> {
-        let (_, _, mut width, mut height) = self.get_window_geometry(&window_id)?;
+    /// captures the window of the given dimensions, cuts away the margins of the calibration.
+    /// The dimensions are passed in, so that a capture needs no extra round trip for the geometry
+    fn capture(
+        &self,
+        window_id: WindowId,
+        (mut width, mut height): (u16, u16),
+        calibration: Option<&Calibration>,
+    ) -> Result<ImageOnHeap> {
         let (mut x, mut y) = (0_i16, 0_i16);
-        if let Some(margin) = self.margin.as_ref() {
+        if let Some(margin) = calibration.and_then(|c| c.margin.as_ref()) {
             if !margin.is_zero() {
                 width -= margin.left + margin.right;
                 height -= margin.top + margin.bottom;
@@ -182,16 +158,19 @@ impl PlatformApi for X11Api {
                 window_id
             ))?;
 
-        let mut raw_data = image.data;
-        convert_bgra_to_rgba(&mut raw_data);
-
-        let color = ColorType::Rgba8;
-        let channels = 4;
-        let mut buffer = FlatSamples {
-            samples: raw_data,
-            layout: SampleLayout::row_major_packed(channels, width as u32, height as u32),
-            color_hint: Some(color),
+        // NOTE: Z_PIXMAP on little endian is BGRA, ARGB visuals (depth 32) are premultiplied
+        let alpha = if image.depth == 32 {
+            AlphaMode::Premultiplied
+        } else {
+            AlphaMode::Straight
         };
+        let mut buffer = from_raw_pixels(
+            image.data,
+            ChannelOrder::Bgra,
+            alpha,
+            width as u32,
+            height as u32,
+        );
 
         if image.depth == 24 {
             // NOTE: in this case the alpha channel is 0, but should be set to 0xff
@@ -211,7 +190,7 @@ impl PlatformApi for X11Api {
                 i += buffer.layout.width_stride;
             }
         }
-        if self.margin.is_some() {
+        if calibration.is_some() {
             // once first image is captured, we make sure that transparency is removed
             // even in cases where `margin.is_zero()`
             let mut i = 3;
@@ -231,6 +210,70 @@ impl PlatformApi for X11Api {
         Ok(ImageOnHeap::new(buffer))
     }
 
+    /// captures the window without any margins and identifies them again
+    fn recalibrate(&self, window_id: WindowId, dimensions: (u16, u16)) -> Result<()> {
+        let image = self.capture(window_id, dimensions, None)?;
+        let (width, height) = (image.layout.width, image.layout.height);
+        let margin = identify_transparency(*image)?;
+        self.calibrations.insert(
+            window_id,
+            Calibration {
+                margin,
+                width,
+                height,
+            },
+        );
+
+        Ok(())
+    }
+}
+
+impl PlatformApi for X11Api {
+    /// 1. error if no screenshot is capture-able
+    /// 2. it checks for transparent margins and configures the api
+    ///    to cut them away in further screenshots
+    fn calibrate(&mut self, window_id: WindowId) -> Result<()> {
+        let (_, _, width, height) = self.get_window_geometry(&window_id)?;
+        self.recalibrate(window_id, (width, height))
+    }
+
+    fn window_list(&self) -> Result<WindowList> {
+        let windows = self.get_visible_windows()?;
+        let mut wins = vec![];
+        for window in windows {
+            if let Ok(Some(name)) = self.get_window_name(&window) {
+                let name = if let Ok((_, _, w, h)) = self.get_window_geometry(&window) {
+                    format!("{} ({}x{})", name, w, h)
+                } else {
+                    name
+                };
+                wins.push((Some(name), window));
+            }
+        }
+
+        Ok(wins)
+    }
+
+    fn capture_window_screenshot(&self, window_id: WindowId) -> Result<ImageOnHeap> {
+        let (_, _, width, height) = self.get_window_geometry(&window_id)?;
+        match self.calibrations.get(window_id) {
+            Some(calibration) if !calibration.is_valid_for(width as u32, height as u32) => {
+                debug!(
+                    "Window {} was resized to {}x{}, calibrating again",
+                    window_id, width, height
+                );
+                self.recalibrate(window_id, (width, height))?;
+            }
+            _ => {}
+        }
+
+        self.capture(
+            window_id,
+            (width, height),
+            self.calibrations.get(window_id).as_ref(),
+        )
+    }
+
     fn get_active_window(&self) -> Result<WindowId> {
         let screen = self.screen();
         let conn = &self.conn;
@@ -255,6 +298,38 @@ impl PlatformApi for X11Api {
 
         Ok(window as WindowId)
     }
+
+    fn window_bounds(&self, window_id: WindowId) -> Result<Option<Rect>> {
+        let conn = &self.conn;
+        let window = window_id as Window;
+        let attr = conn.get_window_attributes(window)?.reply()?;
+        if attr.map_state != MapState::VIEWABLE {
+            return Ok(None);
+        }
+        let (_, _, width, height) = self.get_window_geometry(&window_id)?;
+        // NOTE: the geometry is relative to the parent, so we need the position on the root window
+        let position = conn
+            .translate_coordinates(window, self.screen().root, 0, 0)?
+            .reply()?;
+
+        Ok(Some(Rect::new(
+            position.dst_x as i32,
+            position.dst_y as i32,
+            width as u32,
+            height as u32,
+        )))
+    }
+
+    fn screen_bounds(&self) -> Result<Vec<Rect>> {
+        // the root window spans all monitors
+        let screen = self.screen();
+        Ok(vec![Rect::new(
+            0,
+            0,
+            screen.width_in_pixels as u32,
+            screen.height_in_pixels as u32,
+        )])
+    }
 }
 
 #[cfg(feature = "e2e_tests")]
@@ -284,8 +359,9 @@ mod test {
         if alpha == 0 {
             // if that pixel was full transparent, for example on ubuntu / GNOME, caused by the drop shadow
             // then we expect the calibrated image to be smaller and cropped by this area
-            assert!(api.margin.is_some());
-            assert!(!api.margin.as_ref().unwrap().is_zero());
+            let margin = api.calibrations.get(win).and_then(|c| c.margin);
+            assert!(margin.is_some());
+            assert!(!margin.unwrap().is_zero());
             assert!(height > height_new);
             assert!(width > width_new);
         } else {
@@ -315,6 +391,36 @@ mod test {
         Ok(())
     }
 
+    /// a benchmark of the per-frame cost with the calibration cache, against identifying the
+    /// margins for every frame. Run it with a terminal in front:
+    /// `cargo test --features e2e_tests -- --ignored --nocapture bench_capture`
+    #[test]
+    #[ignore]
+    fn bench_capture_with_calibration_cache() -> Result<()> {
+        const FRAMES: u32 = 50;
+        let mut api = X11Api::new()?;
+        let win = api.get_active_window()?;
+        api.calibrate(win)?;
+
+        let start = std::time::Instant::now();
+        for _ in 0..FRAMES {
+            api.capture_window_screenshot(win)?;
+        }
+        let cached = start.elapsed() / FRAMES;
+
+        let start = std::time::Instant::now();
+        for _ in 0..FRAMES {
+            api.calibrate(win)?;
+            api.capture_window_screenshot(win)?;
+        }
+        let uncached = start.elapsed() / FRAMES;
+
+        println!("per frame: {:?} cached, {:?} uncached", cached, uncached);
+        assert!(cached < uncached);
+
+        Ok(())
+    }
+
     /// reason for this test is the strange tree of windows:
     /// ```sh
     /// $ xwininfo -root -tree -int
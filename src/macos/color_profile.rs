@@ -0,0 +1,82 @@
+use crate::common::{have_same_primaries, ColorProfiles, Rect};
+use anyhow::{Context, Result};
+use core_foundation_sys::base::CFRelease;
+use core_foundation_sys::data::{CFDataGetBytePtr, CFDataGetLength, CFDataRef};
+use core_foundation_sys::string::CFStringRef;
+use core_graphics::display::CGDisplay;
+use std::os::raw::c_void;
+
+use super::window_id::window_bounds;
+
+type CGColorSpaceRef = *const c_void;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    static kCGColorSpaceSRGB: CFStringRef;
+    fn CGDisplayCopyColorSpace(display: u32) -> CGColorSpaceRef;
+    fn CGColorSpaceCreateWithName(name: CFStringRef) -> CGColorSpaceRef;
+    fn CGColorSpaceCopyICCData(space: CGColorSpaceRef) -> CFDataRef;
+}
+
+///
+/// the color profile of the display the window is on, and the sRGB profile to convert to.
+/// `None` if the display is set to sRGB already, then there is nothing to normalize
+pub fn color_profiles(window_id: u64) -> Result<Option<ColorProfiles>> {
+    let display = display_of(window_bounds(window_id)?);
+    let display_profile = icc_data(unsafe { CGDisplayCopyColorSpace(display) })
+        .with_context(|| format!("Cannot read the color profile of display {}", display))?;
+    let srgb = icc_data(unsafe { CGColorSpaceCreateWithName(kCGColorSpaceSRGB) })
+        .context("Cannot read the sRGB color profile")?;
+
+    if have_same_primaries(&display_profile, &srgb) {
+        return Ok(None);
+    }
+    Ok(Some(ColorProfiles {
+        display: display_profile,
+        srgb,
+    }))
+}
+
+/// the display that shows the center of the window, the main display if the window is not on screen
+fn display_of(window: Option<Rect>) -> u32 {
+    let main = CGDisplay::main().id;
+    let window = match window {
+        Some(window) => window,
+        None => return main,
+    };
+    let (x, y) = (
+        window.x as f64 + window.width as f64 / 2.0,
+        window.y as f64 + window.height as f64 / 2.0,
+    );
+    CGDisplay::active_displays()
+        .ok()
+        .and_then(|displays| {
+            displays.into_iter().find(|id| {
+                let bounds = CGDisplay::new(*id).bounds();
+                x >= bounds.origin.x
+                    && x < bounds.origin.x + bounds.size.width
+                    && y >= bounds.origin.y
+                    && y < bounds.origin.y + bounds.size.height
+            })
+        })
+        .unwrap_or(main)
+}
+
+/// the ICC profile of a color space, releases the color space
+fn icc_data(space: CGColorSpaceRef) -> Option<Vec<u8>> {
+    if space.is_null() {
+        return None;
+    }
+    let data = unsafe { CGColorSpaceCopyICCData(space) };
+    unsafe { CFRelease(space) };
+    if data.is_null() {
+        return None;
+    }
+    let bytes = unsafe {
+        std::slice::from_raw_parts(CFDataGetBytePtr(data), CFDataGetLength(data) as usize)
+    }
+    .to_vec();
+    unsafe { CFRelease(data.cast()) };
+
+    Some(bytes)
+}
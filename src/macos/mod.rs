@@ -1,31 +1,72 @@
+mod color_profile;
 mod core_foundation_sys_patches;
 mod screenshot;
 mod window_id;
 
 use crate::common::identify_transparency::identify_transparency;
 use crate::common::image::crop;
+use crate::common::{Calibration, CalibrationCache, Rect};
 use crate::PlatformApi;
-use crate::{ImageOnHeap, Margin, Result, WindowList};
+use crate::{ImageOnHeap, Result, WindowList};
 
-use anyhow::Context;
+use anyhow::{anyhow, bail, Context};
+use core_graphics::display::CGDisplay;
 use screenshot::capture_window_screenshot;
 use std::env;
-use window_id::window_list;
+use window_id::{window_bounds, window_list};
+
+pub use color_profile::color_profiles;
 
 pub const DEFAULT_SHELL: &str = "/bin/sh";
 
 pub fn setup() -> Result<impl PlatformApi> {
-    Ok(QuartzApi { margin: None })
+    Ok(QuartzApi {
+        calibrations: CalibrationCache::default(),
+    })
+}
+
+/// sets the quality of service class of the current thread, this is best effort.
+/// The macOS scheduler works with QoS classes rather than nice levels, so the nice level is mapped onto them
+pub fn set_thread_priority(nice: i32) -> Result<()> {
+    use libc::qos_class_t::*;
+    let class = match nice {
+        n if n < 0 => QOS_CLASS_USER_INTERACTIVE,
+        0 => QOS_CLASS_DEFAULT,
+        _ => QOS_CLASS_UTILITY,
+    };
+    if unsafe { libc::pthread_set_qos_class_self_np(class, 0) } != 0 {
+        bail!(
+            "Cannot set the capture thread priority to {}: {}",
+            nice,
+            std::io::Error::last_os_error()
+        );
+    }
+
+    Ok(())
 }
 
 struct QuartzApi {
-    margin: Option<Margin>,
+    calibrations: CalibrationCache,
+}
+
+impl QuartzApi {
+    /// identifies the transparent margins of an uncropped screenshot
+    fn recalibrate(&self, window_id: u64, image: &ImageOnHeap) -> Result<Calibration> {
+        let calibration = Calibration {
+            margin: identify_transparency(*image.clone())?,
+            width: image.layout.width,
+            height: image.layout.height,
+        };
+        self.calibrations.insert(window_id, calibration.clone());
+
+        Ok(calibration)
+    }
 }
 
 impl PlatformApi for QuartzApi {
     fn calibrate(&mut self, window_id: u64) -> Result<()> {
         let image = capture_window_screenshot(window_id)?;
-        self.margin = identify_transparency(*image)?;
+        self.recalibrate(window_id, &image)?;
 
         Ok(())
     }
@@ -36,7 +77,14 @@ impl PlatformApi for QuartzApi {
 
     fn capture_window_screenshot(&self, window_id: u64) -> Result<ImageOnHeap> {
         let img = capture_window_screenshot(window_id)?;
-        if let Some(margin) = self.margin.as_ref() {
+        let calibration = match self.calibrations.get(window_id) {
+            Some(calibration) if !calibration.is_valid_for(img.layout.width, img.layout.height) => {
+                // the window was resized, so the margins might have changed
+                Some(self.recalibrate(window_id, &img)?)
+            }
+            calibration => calibration,
+        };
+        if let Some(margin) = calibration.and_then(|c| c.margin).as_ref() {
             if !margin.is_zero() {
                 // in this case we want to crop away the transparent margins
                 return crop(*img, margin);
@@ -57,6 +105,28 @@ impl PlatformApi for QuartzApi {
             .parse::<u64>()
             .context("Cannot parse env variable 'WINDOWID' as number")
     }
+
+    fn window_bounds(&self, window_id: u64) -> Result<Option<Rect>> {
+        window_bounds(window_id)
+    }
+
+    fn screen_bounds(&self) -> Result<Vec<Rect>> {
+        let displays = CGDisplay::active_displays()
+            .map_err(|e| anyhow!("Cannot get the active displays, error code: {}", e))?;
+
+        Ok(displays
+            .into_iter()
+            .map(|id| {
+                let bounds = CGDisplay::new(id).bounds();
+                Rect::new(
+                    bounds.origin.x as i32,
+                    bounds.origin.y as i32,
+                    bounds.size.width as u32,
+                    bounds.size.height as u32,
+                )
+            })
+            .collect())
+    }
 }
 
 #[cfg(feature = "e2e_tests")]
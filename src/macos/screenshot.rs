@@ -1,14 +1,12 @@
-use crate::common::image::convert_bgra_to_rgba;
+use crate::common::image::{from_raw_pixels, AlphaMode, ChannelOrder};
 use crate::ImageOnHeap;
 
 use anyhow::{ensure, Context, Result};
 use core_graphics::display::*;
 use core_graphics::image::CGImageRef;
-use image::flat::SampleLayout;
-use image::{ColorType, FlatSamples};
 
 pub fn capture_window_screenshot(win_id: u64) -> Result<ImageOnHeap> {
-    let (w, h, channels, mut raw_data) = {
+    let (w, h, raw_data) = {
         let image = unsafe {
             CGDisplay::screenshot(
                 CGRectNull,
@@ -38,22 +36,27 @@ pub fn capture_window_screenshot(win_id: u64) -> Result<ImageOnHeap> {
             )
         );
         let byte_per_pixel = (img_ref.bits_per_pixel() / 8) as u8;
+        ensure!(
+            byte_per_pixel == 4,
+            format!(
+                "Unsupported pixel format with {} bytes per pixel of window id {}",
+                byte_per_pixel, win_id
+            )
+        );
         // the actual width based on the buffer dimensions
         let w = byte_per_row / byte_per_pixel as u32;
 
-        (w, h, byte_per_pixel, raw_data)
-    };
-
-    convert_bgra_to_rgba(&mut raw_data);
-
-    let color = ColorType::Rgba8;
-    let buffer = FlatSamples {
-        samples: raw_data,
-        layout: SampleLayout::row_major_packed(channels, w, h),
-        color_hint: Some(color),
+        (w, h, raw_data)
     };
 
-    Ok(ImageOnHeap::new(buffer))
+    // NOTE: CoreGraphics delivers BGRA with premultiplied alpha
+    Ok(ImageOnHeap::new(from_raw_pixels(
+        raw_data,
+        ChannelOrder::Bgra,
+        AlphaMode::Premultiplied,
+        w,
+        h,
+    )))
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+use crate::common::Rect;
 use crate::macos::core_foundation_sys_patches::{
     kCFNumberSInt32Type as I32, kCFNumberSInt64Type as I64, CFBooleanGetValue, CFNumberGetType,
 };
@@ -10,14 +11,18 @@ use core_foundation_sys::number::{
 };
 use core_foundation_sys::string::CFStringGetTypeID;
 use core_graphics::display::*;
+use core_graphics::geometry::{CGPoint, CGRect, CGSize};
 use std::ffi::CStr;
 use std::ops::Deref;
 use std::os::raw::c_void;
 
+extern "C" {
+    fn CGRectMakeWithDictionaryRepresentation(dict: CFDictionaryRef, rect: *mut CGRect) -> bool;
+}
+
 #[derive(Debug)]
 enum DictEntryValue {
     Number(i64),
-    #[allow(dead_code)] // false warning of clippy
     Bool(bool),
     String(String),
     Unknown,
@@ -70,10 +75,56 @@ pub fn window_list() -> Result<WindowList> {
     Ok(win_list)
 }
 
-fn get_from_dict(dict: CFDictionaryRef, key: &str) -> DictEntryValue {
+///
+/// the bounds of a window in screen coordinates, `None` if it is not on screen
+pub fn window_bounds(win_id: u64) -> Result<Option<Rect>> {
+    let window_info =
+        unsafe { CGWindowListCopyWindowInfo(kCGWindowListOptionIncludingWindow, win_id as u32) };
+    if window_info.is_null() {
+        return Err(anyhow!(
+            "Cannot get window info from low level C-API call `CGWindowListCopyWindowInfo` -> null"
+        ));
+    }
+    let mut bounds = None;
+    if unsafe { CFArrayGetCount(window_info) } > 0 {
+        let dic_ref = unsafe { CFArrayGetValueAtIndex(window_info, 0) as CFDictionaryRef };
+        // NOTE: `kCGWindowIsOnscreen` is only present, if the window is on screen
+        let on_screen = matches!(
+            get_from_dict(dic_ref, "kCGWindowIsOnscreen"),
+            DictEntryValue::Bool(true)
+        );
+        if let (true, Some(value)) = (on_screen, get_raw_from_dict(dic_ref, "kCGWindowBounds")) {
+            let mut rect = CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(0.0, 0.0));
+            if unsafe { CGRectMakeWithDictionaryRepresentation(value.cast(), &mut rect) } {
+                bounds = Some(Rect::new(
+                    rect.origin.x as i32,
+                    rect.origin.y as i32,
+                    rect.size.width as u32,
+                    rect.size.height as u32,
+                ));
+            }
+        }
+    }
+
+    unsafe {
+        CFRelease(window_info.cast());
+    }
+
+    Ok(bounds)
+}
+
+fn get_raw_from_dict(dict: CFDictionaryRef, key: &str) -> Option<*const c_void> {
     let key: CFString = key.into();
     let mut value: *const c_void = std::ptr::null();
     if unsafe { CFDictionaryGetValueIfPresent(dict, key.to_void(), &mut value) != 0 } {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn get_from_dict(dict: CFDictionaryRef, key: &str) -> DictEntryValue {
+    if let Some(value) = get_raw_from_dict(dict, key) {
         let type_id: CFTypeID = unsafe { CFGetTypeID(value) };
         if type_id == unsafe { CFNumberGetTypeID() } {
             let value = value as CFNumberRef;
@@ -1,7 +1,11 @@
+mod card;
 mod cli;
+mod clipboard;
 mod common;
 mod decor_effect;
+mod doctor;
 mod generators;
+mod theme;
 mod tips;
 
 mod capture;
@@ -20,23 +24,45 @@ use crate::macos::*;
 #[cfg(target_os = "windows")]
 use crate::windows::*;
 
+use crate::card::{hold_frame, render_card, shift_frames};
 use crate::cli::launch;
-use crate::common::utils::{clear_screen, parse_delay, HumanReadable};
-use crate::common::{Margin, PlatformApi};
-use crate::decor_effect::{apply_big_sur_corner_effect, apply_shadow_effect};
-use crate::generators::{check_for_gif, check_for_mp4, generate_gif, generate_mp4};
+use crate::clipboard::{copy_to_clipboard, ClipboardContent};
+use crate::common::identify_background::{as_hex_color, identify_background_color};
+use crate::common::image::{Flip, Rotation};
+#[cfg(unix)]
+use crate::common::signal::{catch_interrupt, release_interrupt};
+#[cfg(unix)]
+use crate::common::tty::{disable_echo, restore_echo};
+use crate::common::utils::{clear_screen, countdown, parse_delay, HumanReadable};
+use crate::common::{ColorProfiles, Margin, PlatformApi, Rect};
+use crate::decor_effect::{
+    apply_big_sur_corner_effect, apply_downscale, apply_flip, apply_rotation, apply_shadow_effect,
+    apply_srgb_normalization,
+};
+use crate::doctor::doctor;
+use crate::generators::{
+    available_output_formats, ensure_monotonic, estimate, export_frames, export_timings,
+    extrapolate_time, generate, human_bytes, GifOptions, OutputFormat, TIMINGS_EXT,
+};
+use crate::theme::{apply_theme, reset_theme, theme};
 use crate::tips::show_tip;
 
-use crate::capture::capture_thread;
-use crate::utils::{sub_shell_thread, target_file, DEFAULT_EXT, MOVIE_EXT};
+use crate::capture::{capture_frame, capture_thread, CaptureOptions, CaptureStats};
+use crate::utils::{
+    file_name_for, open_file, run_post_command, sub_shell_thread, target_file, time_code_for,
+    IMG_EXT,
+};
 use anyhow::{bail, Context};
+use clap::parser::ValueSource;
 use clap::ArgMatches;
+use image::imageops::FilterType;
 use image::FlatSamples;
 use std::borrow::Borrow;
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::time::{Duration, Instant};
-use std::{env, thread};
+use std::{env, fs, thread};
 use tempfile::TempDir;
 
 pub type Image = FlatSamples<Vec<u8>>;
@@ -62,8 +88,19 @@ fn main() -> Result<()> {
     env_logger::init();
 
     let args = launch();
-    if args.get_flag("list-windows") {
-        return ls_win();
+    match args.subcommand() {
+        Some(("doctor", _)) => return doctor(),
+        Some(("encode", encode_args)) => {
+            return encode(&args, encode_args.get_one::<String>("dir").unwrap())
+        }
+        _ => {}
+    }
+    if args.get_flag("about") {
+        print_about();
+        return Ok(());
+    }
+    if let Some(pattern) = args.get_one::<String>("list-windows") {
+        return ls_win(pattern);
     }
 
     let program: String = {
@@ -76,37 +113,94 @@ fn main() -> Result<()> {
     };
     let (win_id, window_name) = current_win_id(&args)?;
     let mut api = setup()?;
+    if !args.get_flag("quiet") {
+        warn_if_not_active(&api, win_id);
+    }
+    ensure_window_on_screen(&api, win_id)?;
     api.calibrate(win_id)?;
+    let region = args.get_one::<Rect>("region").copied();
+    let gap = *args.get_one::<u32>("gap").unwrap();
+    let mut targets = vec![(api, win_id)];
+    if let Some(win_ids) = args.get_many::<u64>("side-by-side") {
+        for win_id in win_ids.copied() {
+            // every window needs its own calibration
+            let mut api = setup()?;
+            ensure_window_on_screen(&api, win_id)?;
+            api.calibrate(win_id)?;
+            targets.push((api, win_id));
+        }
+    }
+    // a sample frame, exactly as it gets recorded, with calibrated, stitched and cropped windows.
+    // This makes sure that the region fits, before recording anything
+    let sample_frame = match region {
+        Some(_) => Some(capture_frame(&targets, gap, region.as_ref())?),
+        None => None,
+    };
+    let bg_color = background_color(&args, || match sample_frame {
+        Some(frame) => Ok(*frame),
+        None => Ok(*capture_frame(&targets, gap, None)?),
+    })?;
 
-    let force_natural = args.get_flag("natural-mode");
-    let should_generate_gif = !args.get_flag("video-only");
-    let should_generate_video = args.get_flag("video") || args.get_flag("video-only");
-    let (start_delay, end_delay) = (
-        parse_delay(args.get_one::<String>("start-pause"), "start-pause")?,
-        parse_delay(args.get_one::<String>("end-pause"), "end-pause")?,
-    );
-
-    if should_generate_gif {
-        check_for_gif()?;
+    let capture_priority = args.get_one::<i32>("capture-priority").copied();
+    let adaptive_fps = args.get_flag("adaptive-fps");
+    let options = CaptureOptions {
+        natural: args.get_flag("natural-mode"),
+        adaptive_fps,
+        gap,
+        idle_threshold: parse_delay(args.get_one::<String>("idle-threshold"), "idle-threshold")?
+            .unwrap_or_default(),
+        idle_pause: parse_delay(args.get_one::<String>("idle-pause"), "idle-pause")?,
+        max_frames: args.get_one::<u64>("frames").map(|n| *n as usize),
+        on_change: args.get_flag("on-change"),
+        dedup: args.get_flag("dedup"),
+        region,
+    };
+    let mut post = PostProcessing::from_args(&args)?;
+    if args.get_flag("srgb") {
+        post.color_profiles = color_profiles(win_id)?;
     }
-    if should_generate_video {
-        check_for_mp4()?;
+    for format in post.formats.iter() {
+        format.check()?;
     }
 
     // the nice thing is the cleanup on drop
     let tempdir = Arc::new(Mutex::new(
         TempDir::new().context("Cannot create tempdir.")?,
     ));
+    // before the capture starts, so that the countdown is not part of the recording
+    if let Some(secs) = args.get_one::<u64>("countdown") {
+        countdown(*secs);
+    }
     let time_codes = Arc::new(Mutex::new(Vec::new()));
     let (tx, rx) = mpsc::channel();
+    let capture_done = Arc::new(AtomicBool::new(false));
     let photograph = {
         let tempdir = tempdir.clone();
         let time_codes = time_codes.clone();
-        thread::spawn(move || -> Result<()> {
-            capture_thread(&rx, api, win_id, time_codes, tempdir, force_natural)
+        let capture_done = capture_done.clone();
+        thread::spawn(move || -> Result<CaptureStats> {
+            if let Some(nice) = capture_priority {
+                if let Err(e) = set_thread_priority(nice) {
+                    eprintln!("{}", e);
+                }
+            }
+            let stats = capture_thread(&rx, targets, time_codes, tempdir, options);
+            capture_done.store(true, Ordering::SeqCst);
+            stats
         })
     };
-    let interact = thread::spawn(move || -> Result<()> { sub_shell_thread(&program).map(|_| ()) });
+    #[cfg(unix)]
+    catch_interrupt();
+    let theme = args.get_one::<String>("theme").and_then(|name| theme(name));
+    if let Some(theme) = theme {
+        apply_theme(theme);
+    }
+    let output_only = args.get_flag("output-only");
+    #[cfg(unix)]
+    let echo = output_only.then(disable_echo).flatten();
+    let interact = thread::spawn(move || -> Result<()> {
+        sub_shell_thread(&program, !output_only, capture_done).map(|_| ())
+    });
 
     clear_screen();
     io::stdout().flush().unwrap();
@@ -120,8 +214,14 @@ fn main() -> Result<()> {
         } else {
             println!("Recording window id: {}", win_id);
         }
+        println!("Background color: {}", bg_color);
+        let formats = available_output_formats()
+            .iter()
+            .map(|format| format.to_string())
+            .collect::<Vec<_>>();
+        println!("Available output formats: {}", formats.join(", "));
     }
-    if !args.get_flag("quiet") {
+    if !args.get_flag("quiet") && !output_only {
         println!("[t-rec]: Press Ctrl+D to end recording");
     }
     thread::sleep(Duration::from_millis(1250));
@@ -131,8 +231,18 @@ fn main() -> Result<()> {
         .join()
         .unwrap()
         .context("Cannot launch the sub shell")?;
-    tx.send(()).context("Cannot stop the recording thread")?;
-    photograph
+    #[cfg(unix)]
+    release_interrupt();
+    #[cfg(unix)]
+    if let Some(echo) = echo {
+        restore_echo(echo);
+    }
+    if theme.is_some() {
+        reset_theme();
+    }
+    // the capture might have stopped on its own already, e.g. with `--frames`
+    tx.send(()).ok();
+    let stats = photograph
         .join()
         .unwrap()
         .context("Cannot launch the recording thread")?;
@@ -142,57 +252,444 @@ fn main() -> Result<()> {
         "🎆 Applying effects to {} frames (might take a bit)",
         time_codes.lock().unwrap().borrow().len()
     );
+    if adaptive_fps {
+        println!("🎞️ Average frame rate: {:.1} fps", stats.fps());
+    }
     show_tip();
 
-    apply_big_sur_corner_effect(
-        &time_codes.lock().unwrap(),
-        tempdir.lock().unwrap().borrow(),
+    let phases = vec![
+        ("capture".to_string(), stats.duration),
+        ("screenshots".to_string(), stats.busy),
+    ];
+    post_process(&args, post, time_codes, tempdir, bg_color, phases)
+}
+
+///
+/// generates the outputs from a directory of existing frames, without recording anything.
+/// The time codes are taken from the frame file names
+fn encode(args: &ArgMatches, dir: &str) -> Result<()> {
+    let post = PostProcessing::from_args(args)?;
+    for format in post.formats.iter() {
+        format.check()?;
+    }
+    if args.get_flag("srgb") {
+        println!("The display of existing frames is unknown, skipping --srgb");
+    }
+
+    // the effects modify the frames, so they are processed in a copy
+    let tempdir = TempDir::new().context("Cannot create tempdir.")?;
+    let mut time_codes = vec![];
+    for entry in fs::read_dir(dir).with_context(|| format!("Cannot read the frames in {}", dir))? {
+        let entry = entry?;
+        if let Some(tc) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| time_code_for(name, IMG_EXT))
+        {
+            fs::copy(
+                entry.path(),
+                tempdir.path().join(file_name_for(&tc, IMG_EXT)),
+            )
+            .with_context(|| format!("Cannot copy frame {:?}", entry.path()))?;
+            time_codes.push(tc);
+        }
+    }
+    if time_codes.is_empty() {
+        bail!(
+            "There are no frames in {}, frames are named like {}",
+            dir,
+            file_name_for(&0, IMG_EXT)
+        );
+    }
+    time_codes.sort_unstable();
+
+    let first = tempdir.path().join(file_name_for(&time_codes[0], IMG_EXT));
+    let bg_color = background_color(args, || {
+        Ok(image::open(&first)
+            .with_context(|| format!("Cannot open frame {:?}", first))?
+            .into_rgba8()
+            .into_flat_samples())
+    })?;
+    println!(
+        "🎆 Applying effects to {} frames (might take a bit)",
+        time_codes.len()
     );
 
-    if let Some("shadow") = args.get_one::<String>("decor").map(|s| s.as_ref()) {
-        apply_shadow_effect(
+    post_process(
+        args,
+        post,
+        Arc::new(Mutex::new(time_codes)),
+        Arc::new(Mutex::new(tempdir)),
+        bg_color,
+        vec![],
+    )
+}
+
+///
+/// the background color for the decor, `auto` picks it from the edges of a frame
+fn background_color(args: &ArgMatches, frame: impl FnOnce() -> Result<Image>) -> Result<String> {
+    let bg = args.get_one::<String>("bg").unwrap().to_string();
+    if bg == "auto" {
+        Ok(identify_background_color(frame()?)?
+            .map(|color| as_hex_color(&color))
+            .unwrap_or_else(|| "transparent".to_string()))
+    } else {
+        Ok(bg)
+    }
+}
+
+///
+/// applies the effects to the captured frames and generates the outputs
+fn post_process(
+    args: &ArgMatches,
+    post: PostProcessing,
+    time_codes: Arc<Mutex<Vec<u128>>>,
+    tempdir: Arc<Mutex<TempDir>>,
+    bg_color: String,
+    mut phases: Vec<(String, Duration)>,
+) -> Result<()> {
+    let PostProcessing {
+        formats,
+        start_delay,
+        end_delay,
+        title,
+        title_delay,
+        end_card,
+        end_card_delay,
+        flip,
+        rotation,
+        max_width,
+        max_height,
+        resample,
+        color_profiles,
+    } = post;
+    let gif_options = GifOptions {
+        // only on request, the default background is transparent too, but the gif stays as it always was
+        transparent: bg_color == "transparent"
+            && args.value_source("bg") == Some(ValueSource::CommandLine),
+        colors: args.get_one::<u32>("gif-colors").copied(),
+        dither: args.get_one::<String>("gif-dither").map(|d| d == "on"),
+        global_palette: args.get_flag("gif-global-palette"),
+    };
+    if ensure_monotonic(&mut time_codes.lock().unwrap()) {
+        log::warn!("Frames were captured out of order, sorted them by their time code");
+    }
+    let mut start_delay = start_delay;
+    let mut end_delay = end_delay;
+    let effects_time = prof! {
+        // transformations go first, so that cards and decors are not affected
+        if let Some(direction) = flip {
+            apply_flip(&time_codes.lock().unwrap(), tempdir.lock().unwrap().borrow(), direction);
+        };
+        if let Some(rotation) = rotation {
+            apply_rotation(&time_codes.lock().unwrap(), tempdir.lock().unwrap().borrow(), rotation);
+        };
+        if max_width.is_some() || max_height.is_some() {
+            apply_downscale(
+                &time_codes.lock().unwrap(),
+                tempdir.lock().unwrap().borrow(),
+                max_width,
+                max_height,
+                resample,
+            );
+        };
+        if let Some(title) = title {
+            let mut time_codes = time_codes.lock().unwrap();
+            // the title card takes the synthesized leading time code 0, the real frames move
+            // later by the start pause, so that the first of them keeps it.
+            // A first frame at 0, e.g. from `encode`, moves at least a bit to make room
+            if let Some(&first) = time_codes.first() {
+                let start_pause = start_delay.unwrap_or_default().as_millis();
+                let offset = if first == 0 { start_pause.max(1) } else { start_pause };
+                shift_frames(&mut time_codes, tempdir.lock().unwrap().borrow(), offset)?;
+                render_card(title, tempdir.lock().unwrap().borrow(), time_codes[0], 0)?;
+                time_codes.insert(0, 0);
+                start_delay = title_delay;
+            }
+        };
+        if let Some(end_card) = end_card {
+            let mut time_codes = time_codes.lock().unwrap();
+            // the last real frame keeps the end pause, with a copy of it that is shown that long.
+            // The end card takes a synthesized trailing time code after it, for its own pause only
+            if let Some(&last) = time_codes.last() {
+                let end_pause = end_delay.unwrap_or_default().as_millis();
+                let mut card_tc = last;
+                if end_pause > 0 {
+                    hold_frame(tempdir.lock().unwrap().borrow(), last, last + end_pause)?;
+                    time_codes.push(last + end_pause);
+                    card_tc += end_pause;
+                }
+                card_tc += end_card_delay.unwrap_or_default().as_millis().max(1);
+                render_card(end_card, tempdir.lock().unwrap().borrow(), last, card_tc)?;
+                time_codes.push(card_tc);
+                end_delay = None;
+            }
+        };
+
+        if let Some(profiles) = color_profiles.as_ref() {
+            apply_srgb_normalization(
+                &time_codes.lock().unwrap(),
+                tempdir.lock().unwrap().borrow(),
+                profiles,
+            )?;
+        } else if args.get_flag("srgb") && args.get_flag("verbose") {
+            println!("Colors are already sRGB, skipping --srgb");
+        };
+
+        apply_big_sur_corner_effect(
             &time_codes.lock().unwrap(),
             tempdir.lock().unwrap().borrow(),
-            args.get_one::<String>("bg").unwrap().to_string(),
-        )
+        );
+
+        if let Some("shadow") = args.get_one::<String>("decor").map(|s| s.as_ref()) {
+            apply_shadow_effect(
+                &time_codes.lock().unwrap(),
+                tempdir.lock().unwrap().borrow(),
+                bg_color,
+            )
+        };
+    };
+    phases.push(("effects".to_string(), effects_time));
+
+    if args.get_flag("estimate") {
+        for format in formats.iter() {
+            let start = Instant::now();
+            let estimation = estimate(
+                *format,
+                &time_codes.lock().unwrap(),
+                tempdir.lock().unwrap().borrow(),
+                &gif_options,
+            )?;
+            println!(
+                "📏 {}: ~{} in ~{}, sampled {} of {} frames",
+                format,
+                human_bytes(estimation.bytes),
+                extrapolate_time(start.elapsed(), &estimation).as_human_readable(),
+                estimation.sampled_frames,
+                estimation.total_frames
+            );
+        }
+        return Ok(());
     }
 
     let target = target_file(args.get_one::<String>("file").unwrap());
     let mut time = Duration::default();
 
-    if should_generate_gif {
-        time += prof! {
-            generate_gif(
+    for format in formats.iter() {
+        let encode_time = prof! {
+            generate(
+                *format,
                 &time_codes.lock().unwrap(),
                 tempdir.lock().unwrap().borrow(),
-                &format!("{}.{}", target, DEFAULT_EXT),
+                &target,
                 start_delay,
-                end_delay
+                end_delay,
+                &gif_options
             )?;
         };
+        time += encode_time;
+        phases.push((format!("{} encode", format), encode_time));
     }
 
-    if should_generate_video {
-        time += prof! {
-            generate_mp4(
-                &time_codes.lock().unwrap(),
-                tempdir.lock().unwrap().borrow(),
-                &format!("{}.{}", target, MOVIE_EXT),
-            )?;
-        }
+    if let Some(dir) = args.get_one::<String>("frames-dir") {
+        export_frames(
+            &time_codes.lock().unwrap(),
+            tempdir.lock().unwrap().path(),
+            dir,
+            start_delay,
+            end_delay,
+            formats.contains(&OutputFormat::Gif),
+        )?;
+    }
+    if args.get_flag("export-timings") {
+        export_timings(
+            &time_codes.lock().unwrap(),
+            start_delay,
+            end_delay,
+            formats.contains(&OutputFormat::Gif),
+            format!("{}.{}", target, TIMINGS_EXT),
+        )?;
     }
 
     println!("Time: {}", time.as_human_readable());
+    if args.get_flag("stats") {
+        print_stats(&phases);
+    }
+
+    let outputs = formats
+        .iter()
+        .map(|format| format.file_name(&target))
+        .collect::<Vec<_>>();
+    if let Some(content) = args.get_one::<String>("copy") {
+        let content = match content.as_str() {
+            "file" => ClipboardContent::File,
+            _ => ClipboardContent::Path,
+        };
+        if let Some(output) = outputs.first() {
+            copy_to_clipboard(output, content)?;
+            println!("📋 Copied {} to the clipboard", output);
+        }
+    }
+    if let Some(command) = args.get_one::<String>("post-command") {
+        let status = run_post_command(command, &outputs)?;
+        if !status.success() {
+            eprintln!("⚠️ The post command failed with {}", status);
+        }
+    }
+    if args.get_flag("open") && !args.get_flag("quiet") {
+        if let Some(output) = outputs.first() {
+            // the outputs are written already, a missing viewer is no reason to fail
+            if let Err(e) = open_file(output) {
+                eprintln!("⚠️ {}", e);
+            }
+        }
+    }
 
     Ok(())
 }
 
+/// everything that happens to the frames after the capture,
+/// parsed upfront so that invalid arguments fail before the recording
+struct PostProcessing<'a> {
+    formats: Vec<OutputFormat>,
+    start_delay: Option<Duration>,
+    end_delay: Option<Duration>,
+    title: Option<&'a String>,
+    title_delay: Option<Duration>,
+    end_card: Option<&'a String>,
+    end_card_delay: Option<Duration>,
+    flip: Option<Flip>,
+    rotation: Option<Rotation>,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+    resample: FilterType,
+    // only known while recording, the profiles belong to the display of the window
+    color_profiles: Option<ColorProfiles>,
+}
+
+impl<'a> PostProcessing<'a> {
+    fn from_args(args: &'a ArgMatches) -> Result<Self> {
+        let formats = output_formats(args);
+        let (start_delay, end_delay) = (
+            parse_delay(args.get_one::<String>("start-pause"), "start-pause")?,
+            parse_delay(args.get_one::<String>("end-pause"), "end-pause")?,
+        );
+        let title = args.get_one::<String>("title");
+        let title_delay = parse_delay(args.get_one::<String>("title-pause"), "title-pause")?;
+        let end_card = args.get_one::<String>("end-card");
+        let end_card_delay =
+            parse_delay(args.get_one::<String>("end-card-pause"), "end-card-pause")?;
+        let flip = args
+            .get_one::<String>("flip")
+            .map(|flip| match flip.as_str() {
+                "horizontal" => Flip::Horizontal,
+                "vertical" => Flip::Vertical,
+                _ => Flip::Both,
+            });
+        let rotation = args
+            .get_one::<String>("rotate")
+            .map(|degrees| match degrees.as_str() {
+                "90" => Rotation::Rotate90,
+                "180" => Rotation::Rotate180,
+                _ => Rotation::Rotate270,
+            });
+        let (max_width, max_height) = (
+            args.get_one::<u32>("max-width").copied(),
+            args.get_one::<u32>("max-height").copied(),
+        );
+        let resample = match args.get_one::<String>("resample").unwrap().as_str() {
+            "nearest" => FilterType::Nearest,
+            "triangle" => FilterType::Triangle,
+            "catmull-rom" => FilterType::CatmullRom,
+            _ => FilterType::Lanczos3,
+        };
+
+        Ok(Self {
+            formats,
+            start_delay,
+            end_delay,
+            title,
+            title_delay,
+            end_card,
+            end_card_delay,
+            flip,
+            rotation,
+            max_width,
+            max_height,
+            resample,
+            color_profiles: None,
+        })
+    }
+}
+
+///
+/// prints everything that is needed to triage a bug report
+fn print_about() {
+    println!("t-rec {}", env!("CARGO_PKG_VERSION"));
+    println!("platform: {} {}", env::consts::OS, env::consts::ARCH);
+    let features = [("e2e_tests", cfg!(feature = "e2e_tests"))]
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(feature, _)| *feature)
+        .collect::<Vec<_>>();
+    if features.is_empty() {
+        println!("features: none");
+    } else {
+        println!("features: {}", features.join(", "));
+    }
+    for format in OutputFormat::ALL.iter() {
+        println!(
+            "{}: {}",
+            format,
+            format
+                .tool_version()
+                .unwrap_or_else(|| "not installed".to_string())
+        );
+    }
+}
+
+///
+/// prints the time spent in each phase of the recording
+fn print_stats(phases: &[(String, Duration)]) {
+    println!("📊 Stats:");
+    for (phase, duration) in phases {
+        println!("  {:<12} {}", phase, duration.as_human_readable());
+    }
+}
+
+///
+/// maps the cli flags to the output formats that should be generated
+fn output_formats(args: &ArgMatches) -> Vec<OutputFormat> {
+    let mut formats = vec![];
+    if !args.get_flag("video-only") {
+        formats.push(OutputFormat::Gif);
+    }
+    if args.get_flag("video") || args.get_flag("video-only") {
+        formats.push(OutputFormat::Mp4);
+    }
+    if args.get_flag("apng") {
+        formats.push(OutputFormat::Apng);
+    }
+    if args.get_flag("webm") {
+        formats.push(OutputFormat::Webm);
+    }
+    if args.get_flag("avif") {
+        formats.push(OutputFormat::Avif);
+    }
+
+    formats
+}
+
 ///
 /// determines the WindowId either by env var 'WINDOWID'
 /// or by the env var 'TERM_PROGRAM' and then asking the window manager for all visible windows
 /// and finding the Terminal in that list
 /// panics if WindowId was not was not there
 fn current_win_id(args: &ArgMatches) -> Result<(WindowId, Option<String>)> {
+    if let Some(title) = args.get_one::<String>("window") {
+        let (win_id, name) = get_window_id_by_title(title)?;
+        return Ok((win_id, Some(name)));
+    }
     match args
         .get_one::<u64>("win-id")
         .ok_or_else(|| env::var("WINDOWID"))
@@ -202,8 +699,8 @@ fn current_win_id(args: &ArgMatches) -> Result<(WindowId, Option<String>)> {
             let terminal = env::var("TERM_PROGRAM").context(
                 "Env variable 'TERM_PROGRAM' was empty but is needed for figure out the WindowId. Please set it to e.g. TERM_PROGRAM=alacitty",
             );
-            if terminal.is_ok() {
-                let (win_id, name) = get_window_id_for(terminal.unwrap()).context(
+            if let Ok(terminal) = terminal {
+                let (win_id, name) = get_window_id_for(terminal).context(
                     "Cannot determine the WindowId of this terminal. Please set env variable 'WINDOWID' and try again.",
                 )?;
                 Ok((win_id, Some(name)))
@@ -236,10 +733,106 @@ pub fn get_window_id_for(terminal: String) -> Result<(WindowId, String)> {
 }
 
 ///
-/// lists all windows with name and id
-pub fn ls_win() -> Result<()> {
+/// finds the one window whose name contains the given title (case-insensitive)
+pub fn get_window_id_by_title(title: &str) -> Result<(WindowId, String)> {
     let api = setup()?;
-    let mut list = api.window_list()?;
+    let mut matches = filter_windows(api.window_list()?, title);
+    match matches.len() {
+        0 => bail!(
+            "Cannot find a window matching {:?}, see `t-rec --ls` for all windows",
+            title
+        ),
+        1 => {
+            let (name, win_id) = matches.remove(0);
+            Ok((win_id, name.unwrap_or_default()))
+        }
+        _ => {
+            matches.sort();
+            let candidates = matches
+                .iter()
+                .map(|(name, id)| format!("  {} | {}", name.as_deref().unwrap_or_default(), id))
+                .collect::<Vec<_>>();
+            bail!(
+                "{} windows match {:?}, please be more specific or use `--win-id`:\n{}",
+                matches.len(),
+                title,
+                candidates.join("\n")
+            )
+        }
+    }
+}
+
+///
+/// keeps only the windows whose name contains the pattern (case-insensitive)
+fn filter_windows(list: WindowList, pattern: &str) -> WindowList {
+    let pattern = pattern.to_lowercase();
+    list.into_iter()
+        .filter(|(name, _)| {
+            name.as_ref()
+                .is_some_and(|name| name.to_lowercase().contains(&pattern))
+        })
+        .collect()
+}
+
+///
+/// warns if the window to record is not the active one,
+/// because that is most often a stale `WINDOWID` pointing somewhere else
+fn warn_if_not_active(api: &impl PlatformApi, win_id: WindowId) {
+    let active = match api.get_active_window() {
+        Ok(active) if active != win_id => active,
+        _ => return,
+    };
+    let name_of = |id: WindowId| -> String {
+        api.window_list()
+            .ok()
+            .and_then(|list| list.into_iter().find(|(_, window_id)| *window_id == id))
+            .and_then(|(name, _)| name)
+            .unwrap_or_else(|| format!("{}", id))
+    };
+    eprintln!(
+        "⚠️ The window to record {:?} is not the active window {:?}, is `WINDOWID` or `--win-id` correct?",
+        name_of(win_id),
+        name_of(active)
+    );
+}
+
+///
+/// makes sure the window is visible on a screen, otherwise the recording would be all black
+fn ensure_window_on_screen(api: &impl PlatformApi, win_id: WindowId) -> Result<()> {
+    match api.window_bounds(win_id)? {
+        None => bail!(
+            "The window {} is not visible on screen, is it minimized or hidden?",
+            win_id
+        ),
+        Some(bounds) if bounds.is_empty() => bail!(
+            "The window {} has no size ({}x{}), there is nothing to record",
+            win_id,
+            bounds.width,
+            bounds.height
+        ),
+        Some(bounds) => {
+            if !api
+                .screen_bounds()?
+                .iter()
+                .any(|screen| screen.intersects(&bounds))
+            {
+                bail!(
+                    "The window {} at {},{} is off-screen, please move it onto a screen",
+                    win_id,
+                    bounds.x,
+                    bounds.y
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+///
+/// lists all windows with name and id, whose name contains the pattern
+pub fn ls_win(pattern: &str) -> Result<()> {
+    let api = setup()?;
+    let mut list = filter_windows(api.window_list()?, pattern);
     list.sort();
 
     println!("Window | Id");
@@ -251,3 +844,28 @@ pub fn ls_win() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_filter_windows_case_insensitive() {
+        let list = vec![
+            (Some("Alacritty".to_string()), 1),
+            (Some("Google Chrome".to_string()), 2),
+            (None, 3),
+            (Some("alacritty - vim".to_string()), 4),
+        ];
+
+        let filtered = filter_windows(list, "ALACRITTY");
+
+        assert_eq!(
+            filtered,
+            vec![
+                (Some("Alacritty".to_string()), 1),
+                (Some("alacritty - vim".to_string()), 4),
+            ]
+        );
+    }
+}
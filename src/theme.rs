@@ -0,0 +1,109 @@
+//! Terminals render the same program with different color themes, so recordings from different machines look different.
+//! A theme forces a known palette via OSC escape sequences while recording, and resets it afterwards.
+//! This works with most terminals (xterm, iTerm2, Alacritty, kitty, WezTerm, GNOME Terminal), but not with macOS Terminal.app.
+
+use std::io::{self, Write};
+
+/// a 16-color ANSI palette with the default foreground and background, as `0xRRGGBB`
+pub struct Theme {
+    pub fg: u32,
+    pub bg: u32,
+    pub palette: [u32; 16],
+}
+
+pub const THEMES: [(&str, Theme); 3] = [
+    (
+        "xterm",
+        Theme {
+            fg: 0xe5e5e5,
+            bg: 0x000000,
+            palette: [
+                0x000000, 0xcd0000, 0x00cd00, 0xcdcd00, 0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5,
+                0x7f7f7f, 0xff0000, 0x00ff00, 0xffff00, 0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+            ],
+        },
+    ),
+    (
+        "solarized-dark",
+        Theme {
+            fg: 0x839496,
+            bg: 0x002b36,
+            palette: [
+                0x073642, 0xdc322f, 0x859900, 0xb58900, 0x268bd2, 0xd33682, 0x2aa198, 0xeee8d5,
+                0x002b36, 0xcb4b16, 0x586e75, 0x657b83, 0x839496, 0x6c71c4, 0x93a1a1, 0xfdf6e3,
+            ],
+        },
+    ),
+    (
+        "dracula",
+        Theme {
+            fg: 0xf8f8f2,
+            bg: 0x282a36,
+            palette: [
+                0x21222c, 0xff5555, 0x50fa7b, 0xf1fa8c, 0xbd93f9, 0xff79c6, 0x8be9fd, 0xf8f8f2,
+                0x6272a4, 0xff6e6e, 0x69ff94, 0xffffa5, 0xd6acff, 0xff92df, 0xa4ffff, 0xffffff,
+            ],
+        },
+    ),
+];
+
+/// the names of all themes, e.g. for the cli
+pub fn theme_names() -> Vec<&'static str> {
+    THEMES.iter().map(|(name, _)| *name).collect()
+}
+
+/// the theme with the given name
+pub fn theme(name: &str) -> Option<&'static Theme> {
+    THEMES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, theme)| theme)
+}
+
+/// switches the terminal to the theme
+pub fn apply_theme(theme: &Theme) {
+    print!("{}", theme_sequence(theme));
+    io::stdout().flush().ok();
+}
+
+/// switches the terminal back to its own palette, foreground and background
+pub fn reset_theme() {
+    print!("\x1b]104\x07\x1b]110\x07\x1b]111\x07");
+    io::stdout().flush().ok();
+}
+
+fn theme_sequence(theme: &Theme) -> String {
+    let mut sequence = String::new();
+    for (i, color) in theme.palette.iter().enumerate() {
+        sequence.push_str(&format!("\x1b]4;{};{}\x07", i, as_x_color(*color)));
+    }
+    sequence.push_str(&format!("\x1b]10;{}\x07", as_x_color(theme.fg)));
+    sequence.push_str(&format!("\x1b]11;{}\x07", as_x_color(theme.bg)));
+    sequence
+}
+
+/// formats a color the way the OSC sequences expect it, e.g. `rgb:1e/1e/1e`
+fn as_x_color(color: u32) -> String {
+    format!(
+        "rgb:{:02x}/{:02x}/{:02x}",
+        (color >> 16) & 0xff,
+        (color >> 8) & 0xff,
+        color & 0xff
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_theme_sequence() {
+        let theme = theme("dracula").unwrap();
+
+        let sequence = theme_sequence(theme);
+
+        assert!(sequence.starts_with("\x1b]4;0;rgb:21/22/2c\x07\x1b]4;1;rgb:ff/55/55\x07"));
+        assert!(sequence.ends_with("\x1b]10;rgb:f8/f8/f2\x07\x1b]11;rgb:28/2a/36\x07"));
+        assert_eq!(sequence.matches("\x1b]4;").count(), 16);
+    }
+}
@@ -1,9 +1,18 @@
+use crate::generators::OutputFormat;
 use anyhow::{Context, Result};
 use std::ffi::OsStr;
-use std::process::{Command, ExitStatus};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 pub const DEFAULT_EXT: &str = "gif";
 pub const MOVIE_EXT: &str = "mp4";
+/// animated pngs are regular pngs for everything that does not know about the animation
+pub const APNG_EXT: &str = "png";
+pub const WEBM_EXT: &str = "webm";
+pub const AVIF_EXT: &str = "avif";
 pub const IMG_EXT: &str = "bmp";
 
 /// encapsulate the file naming convention
@@ -11,14 +20,102 @@ pub fn file_name_for(tc: &u128, ext: &str) -> String {
     format!("t-rec-frame-{:09}.{}", tc, ext)
 }
 
-/// starts the main program and keeps interacting with the user
-/// blocks until termination
-pub fn sub_shell_thread<T: AsRef<OsStr> + Clone>(program: T) -> Result<ExitStatus> {
-    Command::new(program.clone())
+/// the time code of a frame file, the inverse of [`file_name_for`]
+pub fn time_code_for(file_name: &str, ext: &str) -> Option<u128> {
+    file_name
+        .strip_prefix("t-rec-frame-")?
+        .strip_suffix(ext)?
+        .strip_suffix('.')?
+        .parse()
+        .ok()
+}
+
+/// starts the main program and keeps interacting with the user, unless it is not `interactive`,
+/// then it gets no input at all.
+/// blocks until termination, or until `done` is set, e.g. when the capture stopped on its own.
+/// In the latter case the program gets terminated.
+pub fn sub_shell_thread<T: AsRef<OsStr> + Clone>(
+    program: T,
+    interactive: bool,
+    done: Arc<AtomicBool>,
+) -> Result<ExitStatus> {
+    let mut cmd = Command::new(program.clone());
+    if !interactive {
+        cmd.stdin(Stdio::null());
+    }
+    let mut child = cmd
         .spawn()
-        .context(format!("failed to start {:?}", program.as_ref()))?
-        .wait()
-        .context("Something went wrong waiting for the sub shell.")
+        .context(format!("failed to start {:?}", program.as_ref()))?;
+    loop {
+        if let Some(status) = child
+            .try_wait()
+            .context("Something went wrong waiting for the sub shell.")?
+        {
+            return Ok(status);
+        }
+        if done.load(Ordering::SeqCst) {
+            terminate(&mut child);
+            return child
+                .wait()
+                .context("Something went wrong waiting for the sub shell.");
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// hangs up the program, like closing the terminal would, so that it can clean up
+#[cfg(unix)]
+fn terminate(child: &mut Child) {
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, libc::SIGHUP);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate(child: &mut Child) {
+    child.kill().ok();
+}
+
+#[cfg(target_os = "macos")]
+const OPEN_CMD: &str = "open";
+#[cfg(not(target_os = "macos"))]
+const OPEN_CMD: &str = "xdg-open";
+
+/// opens a file with the default application, e.g. the image viewer for a gif
+pub fn open_file(file: impl AsRef<OsStr>) -> Result<()> {
+    let file = file.as_ref();
+    let status = Command::new(OPEN_CMD)
+        .arg(file)
+        .status()
+        .with_context(|| format!("Cannot start '{OPEN_CMD}' to open {:?}", file))?;
+    if !status.success() {
+        anyhow::bail!("'{OPEN_CMD}' failed to open {:?}", file);
+    }
+
+    Ok(())
+}
+
+/// runs a user given shell command after the output was generated,
+/// every `{}` is replaced by the output file(s), quoted for the shell.
+///
+/// Note: the command itself is passed to `sh -c` as is, so it must be trusted like any
+/// other shell command the user types. Only the file names are quoted.
+pub fn run_post_command(command: &str, outputs: &[String]) -> Result<ExitStatus> {
+    let command = with_outputs(command, outputs);
+    Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .status()
+        .with_context(|| format!("Cannot run the post command '{command}'"))
+}
+
+fn with_outputs(command: &str, outputs: &[String]) -> String {
+    let outputs = outputs
+        .iter()
+        .map(|o| format!("'{}'", o.replace('\'', "'\\''")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    command.replace("{}", &outputs)
 }
 
 /// returns a new filename that does not yet exists.
@@ -28,12 +125,38 @@ pub fn target_file(basename: impl AsRef<str>) -> String {
     let basename = basename.as_ref();
     let mut suffix = "".to_string();
     let mut i = 0;
-    while std::path::Path::new(format!("{basename}{suffix}.{DEFAULT_EXT}").as_str()).exists()
-        || std::path::Path::new(format!("{basename}{suffix}.{MOVIE_EXT}").as_str()).exists()
-    {
+    while OutputFormat::ALL.iter().any(|format| {
+        std::path::Path::new(format!("{basename}{suffix}.{}", format.extension()).as_str()).exists()
+    }) {
         i += 1;
         suffix = format!("_{}", i).to_string();
     }
 
     format!("{basename}{suffix}")
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_time_code_from_file_name() {
+        assert_eq!(
+            time_code_for(&file_name_for(&1250, IMG_EXT), IMG_EXT),
+            Some(1250)
+        );
+        assert_eq!(time_code_for("t-rec-frame-000000042.gif", IMG_EXT), None);
+        assert_eq!(time_code_for("t-rec.gif", DEFAULT_EXT), None);
+    }
+
+    #[test]
+    fn should_replace_placeholder_with_quoted_outputs() {
+        let outputs = vec!["t-rec.gif".to_string(), "it's.mp4".to_string()];
+
+        assert_eq!(
+            with_outputs("gh gist create {}", &outputs),
+            "gh gist create 't-rec.gif' 'it'\\''s.mp4'"
+        );
+        assert_eq!(with_outputs("echo done", &outputs), "echo done");
+    }
+}
@@ -1,5 +1,11 @@
+use crate::common::ColorProfiles;
 use crate::{ImageOnHeap, WindowList};
 
+/// captured frames are already in sRGB, there is nothing to normalize
+pub fn color_profiles(_window_id: u64) -> anyhow::Result<Option<ColorProfiles>> {
+    Ok(None)
+}
+
 pub const DEFAULT_SHELL: &str = "cmd.exe";
 
 pub fn window_list() -> anyhow::Result<WindowList> {
@@ -10,5 +16,10 @@ pub fn capture_window_screenshot(_win_id: u64) -> anyhow::Result<ImageOnHeap> {
     unimplemented!("there is only an impl for MacOS")
 }
 
+/// there is no way to set the priority yet, the capture keeps its default priority
+pub fn set_thread_priority(_nice: i32) -> anyhow::Result<()> {
+    anyhow::bail!("setting the capture priority is not supported on this platform")
+}
+
 // references for winRT
 // https://github.com/robmikh/wgc-rust-demo/blob/master/src/main.rs